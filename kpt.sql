@@ -35,15 +35,71 @@ CREATE TABLE ticket (
 
 
 -- Postgres
+DROP TABLE IF EXISTS board_member;
+DROP TABLE IF EXISTS ticket_vote;
+DROP TABLE IF EXISTS action_item;
 DROP TABLE IF EXISTS ticket;
+DROP TABLE IF EXISTS board_column;
 DROP TABLE IF EXISTS board;
+DROP TABLE IF EXISTS api_token;
+DROP TABLE IF EXISTS account_identity;
+DROP TABLE IF EXISTS password_reset_token;
+DROP TABLE IF EXISTS refresh_token;
 DROP TABLE IF EXISTS accounts;
 DROP TABLE IF EXISTS async_sessions;
+DROP TABLE IF EXISTS login_attempt;
 
 CREATE TABLE accounts (
     id BIGSERIAL PRIMARY KEY,
     password VARCHAR(256) NOT NULL,
-    display_name VARCHAR(16) NOT NULL
+    display_name VARCHAR(16) NOT NULL UNIQUE,
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    deleted_at TIMESTAMPTZ
+);
+
+CREATE TABLE password_reset_token (
+    token_hash CHAR(64) PRIMARY KEY,
+    account_id BIGINT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- 外部IdP（OIDC）の subject とアカウントの紐付け
+CREATE TABLE account_identity (
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    account_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (issuer, subject),
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+-- JWTモード（AUTH_MODE=jwt）のリフレッシュトークン
+CREATE TABLE refresh_token (
+    token_hash CHAR(64) PRIMARY KEY,
+    family_id TEXT NOT NULL,
+    account_id BIGINT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX refresh_token_family_idx ON refresh_token (family_id);
+
+-- 個人用アクセストークン（scopes: boards:read / boards:write）
+CREATE TABLE api_token (
+    id BIGSERIAL PRIMARY KEY,
+    account_id BIGINT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
 );
 
 CREATE TABLE board (
@@ -52,23 +108,101 @@ CREATE TABLE board (
     created_by BIGINT NOT NULL,
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    version BIGINT NOT NULL DEFAULT 1,
+    -- 匿名モード（チケットの作成者を返さない）
+    anonymous BOOLEAN NOT NULL DEFAULT FALSE,
+    -- 1人あたりの投票数
+    vote_budget INT NOT NULL DEFAULT 3,
+    -- 投票フェーズが終わるまで票数を返さない
+    hide_votes BOOLEAN NOT NULL DEFAULT FALSE,
+    -- 振り返りの進行フェーズ
+    phase TEXT CHECK (phase IN ('write', 'group', 'vote', 'discuss', 'close')) NOT NULL DEFAULT 'write',
+    -- 匿名チケットの作成者トークンの導出に使う秘密値
+    author_salt TEXT NOT NULL,
     deleted BOOLEAN NOT NULL DEFAULT FALSE,
     FOREIGN KEY (created_by) REFERENCES accounts(id)
 );
 
+-- ボードの列（作成時のテンプレートから作る）
+CREATE TABLE board_column (
+    board_id BIGINT NOT NULL,
+    category TEXT CHECK (category IN (
+        'Keep', 'Problem', 'Try',
+        'Start', 'Stop', 'Continue',
+        'Liked', 'Learned', 'Lacked', 'Longed for',
+        'Mad', 'Sad', 'Glad'
+    )) NOT NULL,
+    position INT NOT NULL,
+    PRIMARY KEY (board_id, category),
+    UNIQUE (board_id, position),
+    FOREIGN KEY (board_id) REFERENCES board(id)
+);
+
 CREATE TABLE ticket (
     id BIGSERIAL PRIMARY KEY,
     board_id BIGINT NOT NULL,
     author_id BIGINT NOT NULL,
-    category TEXT CHECK (category IN ('Keep', 'Problem', 'Try')) NOT NULL,
+    category TEXT NOT NULL,
     content TEXT NOT NULL,
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    version BIGINT NOT NULL DEFAULT 1,
+    -- 列の中での並び順（昇順）
+    position INT NOT NULL DEFAULT 0,
+    -- 匿名モードのボードで作成されたチケット
+    anonymous BOOLEAN NOT NULL DEFAULT FALSE,
     deleted BOOLEAN NOT NULL DEFAULT FALSE,
     FOREIGN KEY (board_id) REFERENCES board(id),
+    FOREIGN KEY (board_id, category) REFERENCES board_column(board_id, category),
     FOREIGN KEY (author_id) REFERENCES accounts(id)
 );
 
+-- Try のチケットから作るアクションアイテム
+CREATE TABLE action_item (
+    id BIGSERIAL PRIMARY KEY,
+    board_id BIGINT NOT NULL,
+    ticket_id BIGINT NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    assignee_id BIGINT NOT NULL,
+    due_date DATE NOT NULL,
+    status TEXT CHECK (status IN ('open', 'done')) NOT NULL DEFAULT 'open',
+    created_by BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (board_id) REFERENCES board(id),
+    FOREIGN KEY (ticket_id) REFERENCES ticket(id),
+    FOREIGN KEY (assignee_id) REFERENCES accounts(id),
+    FOREIGN KEY (created_by) REFERENCES accounts(id)
+);
+
+CREATE INDEX action_item_board_idx ON action_item (board_id);
+CREATE INDEX action_item_assignee_idx ON action_item (assignee_id);
+
+-- ドット投票（1行が1票）
+CREATE TABLE ticket_vote (
+    id BIGSERIAL PRIMARY KEY,
+    ticket_id BIGINT NOT NULL,
+    account_id BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (ticket_id) REFERENCES ticket(id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX ticket_vote_ticket_idx ON ticket_vote (ticket_id);
+CREATE INDEX ticket_vote_account_idx ON ticket_vote (account_id);
+
+CREATE TABLE board_member (
+    board_id BIGINT NOT NULL,
+    account_id BIGINT NOT NULL,
+    role TEXT CHECK (role IN ('owner', 'editor', 'viewer')) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (board_id, account_id),
+    FOREIGN KEY (board_id) REFERENCES board(id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX board_member_account_idx ON board_member (account_id);
+
 
 
 CREATE TABLE async_sessions (
@@ -78,3 +212,12 @@ CREATE TABLE async_sessions (
 );
 
 CREATE INDEX async_sessions_expires_idx ON async_sessions (expires);
+
+-- ログイン失敗回数（LOGIN_RATE_LIMIT_STORE=postgres の場合のみ使用）
+-- key は "name:表示名" または "ip:IPアドレス"
+CREATE TABLE login_attempt (
+    key TEXT PRIMARY KEY,
+    failures INT NOT NULL,
+    locked_until TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,22 @@
+-- 列の導入前に作られたボードに KPT の列を追加する（既存のデータベース向け）
+-- 新規のデータベースは kpt.sql で作成し、作成時にテンプレートの列が入るため不要
+-- 列が1つもないボードだけを対象にするので、何度実行してもよい
+CREATE TABLE IF NOT EXISTS board_column (
+    board_id BIGINT NOT NULL,
+    category TEXT CHECK (category IN (
+        'Keep', 'Problem', 'Try',
+        'Start', 'Stop', 'Continue',
+        'Liked', 'Learned', 'Lacked', 'Longed for',
+        'Mad', 'Sad', 'Glad'
+    )) NOT NULL,
+    position INT NOT NULL,
+    PRIMARY KEY (board_id, category),
+    UNIQUE (board_id, position),
+    FOREIGN KEY (board_id) REFERENCES board(id)
+);
+
+INSERT INTO board_column (board_id, category, position)
+SELECT b.id, c.category, c.position
+FROM board b
+CROSS JOIN (VALUES ('Keep', 0), ('Problem', 1), ('Try', 2)) AS c (category, position)
+WHERE NOT EXISTS (SELECT 1 FROM board_column bc WHERE bc.board_id = b.id);
@@ -0,0 +1,152 @@
+use chrono::{Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{ENV_KEY_AUTH_MODE, ENV_KEY_JWT_ACCESS_TTL_SECS, ENV_KEY_JWT_SECRET};
+use crate::error::AppError;
+
+// Bearerトークンの種類
+// Session: セッションストアのトークン（既定）
+// Jwt: 署名付きの短命なアクセストークン＋リフレッシュトークン
+#[derive(Clone)]
+pub enum AuthMode {
+    Session,
+    Jwt(JwtKeys),
+}
+
+impl AuthMode {
+    // AUTH_MODE=jwt の場合は JWT_SECRET が必須
+    pub fn from_env() -> AuthMode {
+        match std::env::var(ENV_KEY_AUTH_MODE).as_deref() {
+            Ok("jwt") => {
+                let secret = std::env::var(ENV_KEY_JWT_SECRET)
+                    .expect("JWT_SECRET must be set when AUTH_MODE=jwt");
+                assert!(secret.len() >= 32, "JWT_SECRET must be at least 32 bytes");
+                let mut keys = JwtKeys::new(secret.as_bytes());
+                if let Some(secs) = std::env::var(ENV_KEY_JWT_ACCESS_TTL_SECS)
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                {
+                    keys.access_ttl = Duration::seconds(secs);
+                }
+                AuthMode::Jwt(keys)
+            }
+            _ => AuthMode::Session,
+        }
+    }
+}
+
+// HS256 の署名鍵と有効期間
+#[derive(Clone)]
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    pub access_ttl: Duration,
+    pub refresh_ttl: Duration,
+}
+
+// sid はリフレッシュトークンの系列ID（ログアウトで系列ごと失効させる）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub sid: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl JwtKeys {
+    pub fn new(secret: &[u8]) -> JwtKeys {
+        JwtKeys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            access_ttl: Duration::minutes(15),
+            refresh_ttl: Duration::days(30),
+        }
+    }
+
+    pub fn issue(&self, account_id: i64, family_id: &str) -> Result<String, AppError> {
+        let now = Utc::now();
+        self.encode(&Claims {
+            sub: account_id.to_string(),
+            sid: family_id.to_string(),
+            iat: now.timestamp(),
+            exp: (now + self.access_ttl).timestamp(),
+        })
+    }
+
+    pub fn encode(&self, claims: &Claims) -> Result<String, AppError> {
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), claims, &self.encoding)
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    // 署名と有効期限を検証してクレームを返す（HS256 以外は受け付けない）
+    pub fn decode(&self, token: &str) -> Result<Claims, AppError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        validation.set_required_spec_claims(&["exp", "sub"]);
+
+        jsonwebtoken::decode::<Claims>(token, &self.decoding, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => {
+                    AppError::Unauthorized("Access token has expired".to_string())
+                }
+                _ => AppError::Unauthorized("Invalid access token".to_string()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> JwtKeys {
+        JwtKeys::new(b"0123456789abcdef0123456789abcdef")
+    }
+
+    #[test]
+    fn round_trip() {
+        let token = keys().issue(42, "family").unwrap();
+        let claims = keys().decode(&token).unwrap();
+        assert_eq!(claims.sub, "42");
+        assert_eq!(claims.sid, "family");
+    }
+
+    #[test]
+    fn rejects_tampered_payload() {
+        let token = keys().issue(42, "family").unwrap();
+        let forged = keys().issue(1, "family").unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged_parts: Vec<&str> = forged.split('.').collect();
+        let tampered = format!("{}.{}.{}", parts[0], forged_parts[1], parts[2]);
+        assert!(keys().decode(&tampered).is_err());
+    }
+
+    #[test]
+    fn rejects_other_secret() {
+        let token = keys().issue(42, "family").unwrap();
+        let other = JwtKeys::new(b"another-secret-another-secret-00");
+        assert!(other.decode(&token).is_err());
+    }
+
+    #[test]
+    fn rejects_other_algorithm() {
+        let claims = keys().decode(&keys().issue(42, "family").unwrap()).unwrap();
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS512),
+            &claims,
+            &EncodingKey::from_secret(b"0123456789abcdef0123456789abcdef"),
+        )
+        .unwrap();
+        assert!(keys().decode(&token).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let mut keys = keys();
+        keys.access_ttl = Duration::seconds(-1);
+        let token = keys.issue(42, "family").unwrap();
+        assert!(keys.decode(&token).is_err());
+    }
+}
@@ -1,56 +1,378 @@
+use crate::auth::AuthMode;
 use crate::database::Repositories;
-use crate::repos_impl::AccountsImpl;
+use crate::entities::{Account, ApiScope, ApiToken, LoginSession};
+use crate::error::AppError;
+use crate::events::BoardHub;
+use crate::notifier::Notifier;
+use crate::oidc::OidcClient;
+use crate::rate_limit::LoginLimiter;
+use crate::request::{Json, Path, UserContext};
 use crate::services::{self};
-use axum::extract::Json;
-use axum::extract::State;
+use crate::state::AppState;
+use async_sqlx_session::PostgresSessionStore;
+use axum::extract::{ConnectInfo, State};
 use axum::http::StatusCode;
 use axum::{Router, response::IntoResponse, routing};
+use axum_extra::TypedHeader;
+use axum_extra::headers::UserAgent;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-pub fn accounts(repos: Arc<Repositories>) -> Router {
-    let accounts_impl = repos.accounts.clone();
+pub fn accounts(state: AppState) -> Router {
     Router::new()
         .route("/new", routing::post(post))
-        .route("/session", routing::post(api_login))
-        .with_state(accounts_impl)
+        .route("/me", routing::get(me).patch(update_me).delete(delete_me))
+        .route("/session", routing::post(api_login).delete(logout))
+        .route("/refresh", routing::post(refresh))
+        .route("/oidc/login", routing::get(oidc_login))
+        .route("/oidc/callback", routing::post(oidc_callback))
+        .route("/sessions", routing::get(list_sessions))
+        .route("/sessions/:id", routing::delete(revoke_session))
+        .route(
+            "/tokens",
+            routing::get(list_api_tokens).post(create_api_token),
+        )
+        .route("/tokens/:id", routing::delete(revoke_api_token))
+        .route("/password", routing::put(change_password))
+        .route("/password/reset", routing::post(request_password_reset))
+        .route("/password/reset/confirm", routing::post(reset_password))
+        .with_state(state)
 }
 
 async fn post(
-    State(accounts_repo): State<AccountsImpl>,
+    State(repos): State<Arc<Repositories>>,
     Json(payload): Json<SignUpForm>,
-) -> impl IntoResponse {
-    services::create_account(&accounts_repo, &payload.password, &payload.display_name).await;
+) -> Result<impl IntoResponse, AppError> {
+    services::create_account(&repos.accounts, &payload.password, &payload.display_name).await?;
 
-    (
+    Ok((
         StatusCode::CREATED,
         Json(ApiResponse {
             message: "Account created successfully",
         }),
+    ))
+}
+
+async fn me(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<AccountProfile>, AppError> {
+    let account = services::get_account(&repos.accounts, &user_ctx).await?;
+    Ok(Json(AccountProfile::from(account)))
+}
+
+async fn update_me(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<ProfileForm>,
+) -> Result<Json<AccountProfile>, AppError> {
+    let account =
+        services::change_display_name(&repos.accounts, &user_ctx, &payload.display_name).await?;
+    Ok(Json(AccountProfile::from(account)))
+}
+
+// ボードの整理と匿名化は同じトランザクションで行う（途中で失敗すればロールバック）
+// ボードの接続を閉じさせるイベントはコミット後に配信する
+async fn delete_me(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    let tx = repos.begin().await?;
+    let events = hub.deferred();
+    services::delete_account(
+        &repos.accounts.join(&tx),
+        &repos.boards.join(&tx),
+        &repos.tickets.join(&tx),
+        &repos.sessions.join(&tx),
+        &events,
+        &user_ctx,
     )
+    .await?;
+    tx.commit().await?;
+    events.flush();
+
+    Ok(Json(ApiResponse {
+        message: "Account deleted successfully",
+    }))
 }
 
 async fn api_login(
-    State(accounts_repo): State<AccountsImpl>,
+    State(repos): State<Arc<Repositories>>,
+    State(sessions): State<PostgresSessionStore>,
+    State(limiter): State<LoginLimiter>,
+    State(auth): State<AuthMode>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    user_agent: Option<TypedHeader<UserAgent>>,
     Json(payload): Json<SignInForm>,
-) -> impl IntoResponse {
-    let session_token =
-        services::create_session(&accounts_repo, &payload.display_name, &payload.password).await;
-
-    if let Some(token) = session_token {
-        Json(json!({
-            "message": "ログイン成功",
-            "token": token.value(),
-        }))
-        .into_response()
-    } else {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "message": "ログイン失敗" })),
-        )
-            .into_response()
+) -> Result<impl IntoResponse, AppError> {
+    let credentials = services::Credentials {
+        display_name: &payload.display_name,
+        password: &payload.password,
+        user_agent: user_agent.as_ref().map(|TypedHeader(ua)| ua.as_str()),
+        ip: connect_info.map(|ConnectInfo(addr)| addr.ip()),
+    };
+    let account_id = services::authenticate(&repos.accounts, &limiter, &credentials).await?;
+
+    login_response(&repos, &sessions, auth, account_id, credentials.user_agent).await
+}
+
+// 認証方式に応じてセッショントークンまたはトークンの組を返す
+async fn login_response(
+    repos: &Repositories,
+    sessions: &PostgresSessionStore,
+    auth: AuthMode,
+    account_id: i64,
+    user_agent: Option<&str>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    match auth {
+        AuthMode::Session => {
+            let token = services::create_session(sessions, account_id, user_agent).await?;
+            Ok(Json(json!({
+                "message": "ログイン成功",
+                "token": token.value(),
+            })))
+        }
+        AuthMode::Jwt(keys) => {
+            let pair =
+                services::issue_token_pair(&repos.refresh_tokens, &keys, account_id, None).await?;
+            Ok(Json(token_pair_json("ログイン成功", pair)))
+        }
+    }
+}
+
+// クライアントはこの URL へブラウザを遷移させる
+async fn oidc_login(
+    State(oidc): State<Option<OidcClient>>,
+    State(sessions): State<PostgresSessionStore>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let client = oidc.ok_or_else(oidc_disabled)?;
+    let url = services::start_oidc_login(&client, &sessions).await?;
+
+    Ok(Json(json!({ "url": url })))
+}
+
+// redirect_uri で受け取った code と state を渡す
+async fn oidc_callback(
+    State(repos): State<Arc<Repositories>>,
+    State(sessions): State<PostgresSessionStore>,
+    State(auth): State<AuthMode>,
+    State(oidc): State<Option<OidcClient>>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Json(payload): Json<OidcCallbackForm>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let client = oidc.ok_or_else(oidc_disabled)?;
+    let info =
+        services::complete_oidc_login(&client, &sessions, &payload.code, &payload.state).await?;
+
+    // アカウントの作成と外部IDの連携は同じトランザクションで行う
+    let tx = repos.begin().await?;
+    let account_id = services::find_or_provision_account(
+        &repos.accounts.join(&tx),
+        &repos.identities.join(&tx),
+        &client.issuer,
+        &info,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let user_agent = user_agent.as_ref().map(|TypedHeader(ua)| ua.as_str());
+    login_response(&repos, &sessions, auth, account_id, user_agent).await
+}
+
+fn oidc_disabled() -> AppError {
+    AppError::NotFound("OIDC login is not configured".to_string())
+}
+
+// JWTモードのみ：リフレッシュトークンを新しい組に交換する
+async fn refresh(
+    State(repos): State<Arc<Repositories>>,
+    State(auth): State<AuthMode>,
+    Json(payload): Json<RefreshForm>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let AuthMode::Jwt(keys) = auth else {
+        return Err(AppError::NotFound(
+            "Token refresh is only available in JWT mode".to_string(),
+        ));
+    };
+
+    let pair =
+        services::refresh_token_pair(&repos.refresh_tokens, &keys, &payload.refresh_token).await?;
+    Ok(Json(token_pair_json("トークン更新", pair)))
+}
+
+fn token_pair_json(message: &str, pair: services::TokenPair) -> serde_json::Value {
+    json!({
+        "message": message,
+        "token": pair.access_token,
+        "refresh_token": pair.refresh_token,
+        "expires_in": pair.expires_in,
+    })
+}
+
+async fn logout(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    State(auth): State<AuthMode>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    match auth {
+        AuthMode::Session => services::delete_session(&repos.sessions, &user_ctx).await?,
+        AuthMode::Jwt(_) => services::revoke_token_family(&repos.refresh_tokens, &user_ctx).await?,
     }
+
+    Ok(Json(ApiResponse {
+        message: "Logged out successfully",
+    }))
+}
+
+// JWTモードではリフレッシュトークンの系列をセッションとして扱う
+async fn list_sessions(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    State(auth): State<AuthMode>,
+) -> Result<Json<Vec<SessionSummary>>, AppError> {
+    let sessions = match auth {
+        AuthMode::Session => services::get_sessions(&repos.sessions, &user_ctx).await?,
+        AuthMode::Jwt(_) => services::get_token_families(&repos.refresh_tokens, &user_ctx).await?,
+    };
+
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| SessionSummary::new(session, &user_ctx))
+            .collect(),
+    ))
+}
+
+// id はストア上のID（base64 のため URL エンコードして指定する）、JWTモードでは系列ID
+async fn revoke_session(
+    user_ctx: UserContext,
+    Path(session_id): Path<String>,
+    State(repos): State<Arc<Repositories>>,
+    State(auth): State<AuthMode>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    match auth {
+        AuthMode::Session => {
+            services::revoke_session(&repos.sessions, &user_ctx, &session_id).await?
+        }
+        AuthMode::Jwt(_) => {
+            services::revoke_token_family_by_id(&repos.refresh_tokens, &user_ctx, &session_id)
+                .await?
+        }
+    }
+
+    Ok(Json(ApiResponse {
+        message: "Session revoked successfully",
+    }))
+}
+
+// 平文のトークンは作成時のレスポンスにのみ含まれる
+async fn create_api_token(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<ApiTokenForm>,
+) -> Result<impl IntoResponse, AppError> {
+    let (token, api_token) = services::create_api_token(
+        &repos.api_tokens,
+        &user_ctx,
+        &payload.name,
+        payload.scopes,
+        payload.expires_at,
+    )
+    .await?;
+
+    let mut summary = ApiTokenSummary::from(api_token);
+    summary.token = Some(token);
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+async fn list_api_tokens(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<Vec<ApiTokenSummary>>, AppError> {
+    let tokens = services::get_api_tokens(&repos.api_tokens, &user_ctx).await?;
+
+    Ok(Json(
+        tokens.into_iter().map(ApiTokenSummary::from).collect(),
+    ))
+}
+
+async fn revoke_api_token(
+    user_ctx: UserContext,
+    Path(token_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    services::revoke_api_token(&repos.api_tokens, &user_ctx, token_id).await?;
+
+    Ok(Json(ApiResponse {
+        message: "API token revoked successfully",
+    }))
+}
+
+async fn change_password(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<PasswordChangeForm>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    services::change_password(
+        &repos.accounts,
+        &repos.sessions,
+        &repos.refresh_tokens,
+        &user_ctx,
+        &payload.current_password,
+        &payload.new_password,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse {
+        message: "Password changed successfully",
+    }))
+}
+
+// トークンは通知経由でのみ渡し、レスポンスには含めない
+async fn request_password_reset(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+    State(notifier): State<Arc<dyn Notifier>>,
+    Json(payload): Json<PasswordResetRequestForm>,
+) -> Result<impl IntoResponse, AppError> {
+    services::request_password_reset(
+        &repos.accounts,
+        &repos.password_resets,
+        notifier.as_ref(),
+        &user_ctx,
+        &payload.display_name,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ApiResponse {
+            message: "Password reset token issued",
+        }),
+    ))
+}
+
+async fn reset_password(
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<PasswordResetForm>,
+) -> Result<Json<ApiResponse<'static>>, AppError> {
+    services::reset_password(
+        &repos.accounts,
+        &repos.password_resets,
+        &repos.sessions,
+        &repos.refresh_tokens,
+        &payload.token,
+        &payload.new_password,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse {
+        message: "Password reset successfully",
+    }))
 }
 
 #[derive(Deserialize)]
@@ -65,7 +387,113 @@ struct SignUpForm {
     password: String,
 }
 
+#[derive(Deserialize)]
+struct ProfileForm {
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct RefreshForm {
+    refresh_token: String,
+}
+
+#[derive(Deserialize)]
+struct OidcCallbackForm {
+    code: String,
+    state: String,
+}
+
+// scopes を省略するとすべてのスコープ、expires_at を省略すると無期限
+#[derive(Deserialize)]
+struct ApiTokenForm {
+    name: String,
+    scopes: Option<Vec<ApiScope>>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct PasswordChangeForm {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Deserialize)]
+struct PasswordResetRequestForm {
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct PasswordResetForm {
+    token: String,
+    new_password: String,
+}
+
 #[derive(Serialize)]
 struct ApiResponse<'a> {
     message: &'a str,
 }
+
+#[derive(Serialize)]
+struct SessionSummary {
+    id: String,
+    created_at: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+    user_agent: Option<String>,
+    // このリクエストで使われているセッションか
+    current: bool,
+}
+
+impl SessionSummary {
+    fn new(session: LoginSession, user_ctx: &UserContext) -> SessionSummary {
+        SessionSummary {
+            current: session.id == user_ctx.session_id,
+            id: session.id,
+            created_at: session.created_at,
+            expires_at: session.expires_at,
+            user_agent: session.user_agent,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AccountProfile {
+    id: i64,
+    display_name: String,
+    is_admin: bool,
+}
+
+impl From<Account> for AccountProfile {
+    fn from(account: Account) -> Self {
+        AccountProfile {
+            id: account.id().unwrap_or(0),
+            display_name: account.display_name,
+            is_admin: account.is_admin,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ApiTokenSummary {
+    id: i64,
+    name: String,
+    scopes: Vec<ApiScope>,
+    expires_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+}
+
+impl From<ApiToken> for ApiTokenSummary {
+    fn from(api_token: ApiToken) -> Self {
+        ApiTokenSummary {
+            id: api_token.id.unwrap_or(0),
+            name: api_token.name,
+            scopes: api_token.scopes,
+            expires_at: api_token.expires_at,
+            created_at: api_token.created_at,
+            last_used_at: api_token.last_used_at,
+            token: None,
+        }
+    }
+}
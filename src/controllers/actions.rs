@@ -0,0 +1,26 @@
+use super::boards::ActionItemSummary;
+use crate::database::Repositories;
+use crate::error::AppError;
+use crate::request::{Json, UserContext};
+use crate::services;
+use crate::state::AppState;
+use axum::Router;
+use axum::extract::State;
+use axum::routing::get;
+use std::sync::Arc;
+
+pub fn actions(state: AppState) -> Router {
+    Router::new()
+        .route("/mine", get(my_actions))
+        .with_state(state)
+}
+
+pub async fn my_actions(
+    user_ctx: UserContext,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<Vec<ActionItemSummary>>, AppError> {
+    let actions = services::get_my_action_items(&repos.action_items, &user_ctx).await?;
+    Ok(Json(
+        actions.into_iter().map(ActionItemSummary::from).collect(),
+    ))
+}
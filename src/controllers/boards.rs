@@ -1,265 +1,282 @@
 use crate::database::Repositories;
-use crate::repos_impl::BoardsImpl;
-use crate::request::UserContext;
+use crate::entities::{
+    ActionItem, ActionStatus, BoardPhase, BoardRole, BoardSettings, Category, RetroTemplate,
+};
+use crate::error::AppError;
+use crate::events::{BoardEvent, BoardHub};
+use crate::repos_impl::{BoardsImpl, TicketsImpl};
+use crate::request::{Json, Path, Query, UserContext, WsUserContext};
 use crate::services;
+use crate::state::AppState;
 use axum::Router;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
 use axum::http::StatusCode;
-use axum::routing::{delete, get, post};
+use axum::routing::{delete, get, patch, post};
 use axum::{
-    extract::{Json, Path, State},
+    extract::State,
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
-pub fn boards(repos: Arc<Repositories>) -> Router {
+pub fn boards(state: AppState) -> Router {
     Router::new()
         .route("/list", get(all_boards))
         .route("/save", post(save_board_tickets))
         .route("/data/:titleId", get(get_board_data))
         .route("/delete/:titleId", delete(delete_board)) // Assuming delete uses the same endpoint
-        .with_state(repos)
+        .route("/:id/members", get(list_members).post(add_member))
+        .route("/:id/members/:accountId", delete(remove_member))
+        .route("/:id/tickets", post(create_ticket))
+        .route("/:id/settings", patch(update_settings))
+        .route("/:id/phase", post(change_phase))
+        .route("/:id/actions", get(list_actions).post(create_action))
+        .route(
+            "/:id/actions/:actionId",
+            patch(update_action).delete(delete_action),
+        )
+        .route("/:id/ws", get(board_ws))
+        .with_state(state)
 }
 
 async fn all_boards(
     user_ctx: UserContext,
     State(repos): State<Arc<Repositories>>,
-) -> Json<Vec<BoardSummary>> {
+) -> Result<Json<Vec<BoardSummary>>, AppError> {
     let boards_repo = &repos.boards;
-    services::get_all_boards(boards_repo, &user_ctx).await
+    let boards = services::get_all_boards(boards_repo, &user_ctx).await?;
+    Ok(Json(boards))
 }
 
 pub async fn save_board_tickets(
     user_ctx: UserContext,
     State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
     Json(payload): Json<SavePayload>,
-) -> impl IntoResponse {
-    let boards_repo = &repos.boards;
-    let tickets_repo = &repos.tickets;
+) -> Result<impl IntoResponse, AppError> {
+    let tx = repos.begin().await?;
+
+    // ボードとチケットの保存はすべて同じトランザクションで行い、
+    // イベントはコミット後にまとめて配信する
+    let boards_repo = repos.boards.join(&tx);
+    let tickets_repo = repos.tickets.join(&tx);
+    let events = hub.deferred();
+
+    let result =
+        save_board_and_tickets(&boards_repo, &tickets_repo, &events, &user_ctx, &payload).await;
+
+    match result {
+        Ok((status, message, version)) => {
+            tx.commit().await?;
+            events.flush();
+            let mut response = ApiResponse::new(message, payload.title.clone());
+            response.version = Some(version);
+            Ok((status, Json(response)))
+        }
+        Err(e) => {
+            eprintln!("Save failed, rolling back: {}", e);
+            if let Err(e) = tx.rollback().await {
+                eprintln!("Rollback failed: {}", e);
+            }
+
+            // 競合時はクライアントがマージできるようサーバー側の最新状態を返す
+            if let AppError::Conflict { .. } = e
+                && let Some(board_id) = payload.titleId.as_ref().and_then(|id| id.parse().ok())
+                && let Ok(current) = load_board_data(&repos, &user_ctx, board_id, false).await
+            {
+                return Err(e.with_current(current));
+            }
+            Err(e)
+        }
+    }
+}
 
-    if let Some(title_id_str) = payload.titleId.clone() {
-        // titleIdがある場合は更新処理
-        let title_id: i64 = match title_id_str.parse() {
-            Ok(id) => id,
-            Err(_) => {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(ApiResponse {
-                        message: "titleId is invalid".into(),
-                        title: payload.title.clone(),
-                    }),
+// 失敗した時点で中断し、呼び出し元でロールバックする
+// 成功時はステータス・メッセージ・保存後のボードの版を返す
+async fn save_board_and_tickets(
+    boards_repo: &BoardsImpl,
+    tickets_repo: &TicketsImpl,
+    hub: &BoardHub,
+    user_ctx: &UserContext,
+    payload: &SavePayload,
+) -> Result<(StatusCode, String, i64), AppError> {
+    let Some(title_id_str) = payload.titleId.as_ref() else {
+        // titleIdがない → 新規作成処理
+        let board_id = services::save_board(
+            boards_repo,
+            user_ctx,
+            payload.title.clone(),
+            payload.template,
+        )
+        .await?;
+
+        for list in &payload.projectData.lists {
+            let mut ids = Vec::with_capacity(list.tickets.len());
+            for ticket in &list.tickets {
+                let new_ticket = crate::entities::Ticket::create(
+                    board_id,
+                    user_ctx.user_id,
+                    list.category,
+                    ticket.content.clone(),
                 );
+
+                let saved =
+                    services::save_ticket(boards_repo, tickets_repo, hub, user_ctx, new_ticket)
+                        .await?;
+                ids.extend(saved.id);
             }
-        };
-
-        match services::get_board_by_id(boards_repo, &user_ctx, title_id).await {
-            Ok(mut board) => {
-                // ボードのタイトル更新
-                let update_result = services::update_board(
-                    boards_repo,
-                    &user_ctx,
-                    &mut board,
-                    payload.title.clone(),
-                )
-                .await;
-
-                // boardの取得後、update_boardより前でも後でもOK
-                let existing_tickets =
-                    match services::get_all_tickets(tickets_repo, &user_ctx, title_id).await {
-                        Ok(ts) => ts,
-                        Err(e) => {
-                            eprintln!("チケット取得失敗: {}", e);
-                            return (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(ApiResponse {
-                                    message: format!("Failed to fetch existing tickets: {}", e),
-                                    title: payload.title.clone(),
-                                }),
-                            );
-                        }
-                    };
+            // 送られてきた順を列の並び順にする
+            services::reorder_tickets(boards_repo, tickets_repo, user_ctx, board_id, &ids).await?;
+        }
 
-                // クライアント側から送られてきた有効なID一覧を収集
-                let received_ids: HashSet<i64> = payload
-                    .projectData
-                    .lists
-                    .iter()
-                    .flat_map(|list| list.tickets.iter())
-                    .filter_map(|ticket| ticket.id)
-                    .filter(|id| *id != 0)
-                    .collect();
-
-                // DBにあるが、クライアントから来なかった → 削除
-                for ticket in existing_tickets {
-                    if let Some(ticket_id) = ticket.id {
-                        if !received_ids.contains(&ticket_id) {
-                            if let Err(e) =
-                                services::delete_ticket(tickets_repo, &user_ctx, ticket_id).await
-                            {
-                                eprintln!("Failed to delete ticket {}: {}", ticket_id, e);
-                            }
-                        }
-                    }
-                }
+        let version = current_version(boards_repo, user_ctx, board_id).await?;
+        return Ok((
+            StatusCode::CREATED,
+            "Board and tickets created".into(),
+            version,
+        ));
+    };
 
-                // チケット保存処理
-                let mut ticket_errors = vec![];
-
-                for list in &payload.projectData.lists {
-                    for ticket in &list.tickets {
-                        let save_result = if ticket.id == Some(0) {
-                            // 新規チケット作成
-                            let new_ticket = crate::entities::Ticket::create(
-                                title_id,
-                                user_ctx.user_id,
-                                list.category.clone(),
-                                ticket.content.clone(),
-                            );
-                            services::save_ticket(tickets_repo, &user_ctx, new_ticket).await
-                        } else {
-                            // 既存チケット更新
-                            let updated_ticket = crate::entities::Ticket::new(
-                                ticket.id,
-                                title_id,
-                                user_ctx.user_id,
-                                list.category.clone(),
-                                ticket.content.clone(),
-                                chrono::Utc::now().naive_utc(),
-                                chrono::Utc::now().naive_utc(),
-                            );
-                            services::update_ticket(tickets_repo, &user_ctx, updated_ticket).await
-                        };
-
-                        if let Err(e) = save_result {
-                            ticket_errors.push(format!("Ticket {:?} failed: {}", ticket.id, e));
-                        }
-                    }
-                }
+    // titleIdがある場合は更新処理
+    let title_id: i64 = title_id_str
+        .parse()
+        .map_err(|_| AppError::validation("titleId is invalid"))?;
 
-                if let Err(e) = update_result {
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse {
-                            message: format!("Board update failed: {}", e),
-                            title: payload.title.clone(),
-                        }),
-                    );
-                }
+    let mut board = services::get_board_by_id(boards_repo, user_ctx, title_id).await?;
 
-                if ticket_errors.is_empty() {
-                    (
-                        StatusCode::OK,
-                        Json(ApiResponse {
-                            message: "Board and tickets updated".into(),
-                            title: payload.title.clone(),
-                        }),
-                    )
-                } else {
-                    (
-                        StatusCode::OK,
-                        Json(ApiResponse {
-                            message: format!(
-                                "Board updated but some tickets failed: {}",
-                                ticket_errors.join("; ")
-                            ),
-                            title: payload.title.clone(),
-                        }),
-                    )
-                }
-            }
-            Err(_) => (
-                StatusCode::NOT_FOUND,
-                Json(ApiResponse {
-                    message: "Board not found".into(),
-                    title: payload.title.clone(),
-                }),
-            ),
+    // クライアントが読み込んだ版より後に更新されていれば競合
+    // （チケットの変更でもボードの版は進む）
+    if let Some(version) = payload.version {
+        board.version = version;
+    }
+
+    // ボードのタイトル更新
+    services::update_board(boards_repo, user_ctx, &mut board, payload.title.clone()).await?;
+
+    let existing_tickets =
+        services::get_all_tickets(boards_repo, tickets_repo, user_ctx, title_id).await?;
+
+    // クライアント側から送られてきた有効なID一覧を収集
+    let received_ids: HashSet<i64> = payload
+        .projectData
+        .lists
+        .iter()
+        .flat_map(|list| list.tickets.iter())
+        .filter_map(|ticket| ticket.id)
+        .filter(|id| *id != 0)
+        .collect();
+
+    // DBにあるが、クライアントから来なかった → 削除
+    for ticket in &existing_tickets {
+        if let Some(ticket_id) = ticket.id.filter(|id| !received_ids.contains(id)) {
+            services::delete_ticket(boards_repo, tickets_repo, hub, user_ctx, ticket_id).await?;
         }
-    } else {
-        // titleIdがない → 新規作成処理
-        match services::save_board(boards_repo, &user_ctx, payload.title.clone()).await {
-            Ok(board_id) => {
-                let mut ticket_errors = vec![];
-
-                for list in &payload.projectData.lists {
-                    for ticket in &list.tickets {
-                        let new_ticket = crate::entities::Ticket::create(
-                            board_id,
-                            user_ctx.user_id,
-                            list.category.clone(),
-                            ticket.content.clone(),
-                        );
-
-                        if let Err(e) =
-                            services::save_ticket(tickets_repo, &user_ctx, new_ticket).await
-                        {
-                            ticket_errors
-                                .push(format!("Failed to save ticket '{}': {}", ticket.content, e));
-                        }
-                    }
-                }
+    }
 
-                (
-                    StatusCode::CREATED,
-                    Json(ApiResponse {
-                        message: if ticket_errors.is_empty() {
-                            "Board and tickets created".into()
-                        } else {
-                            format!(
-                                "Board created but some tickets failed: {}",
-                                ticket_errors.join("; ")
-                            )
-                        },
-                        title: payload.title.clone(),
-                    }),
-                )
-            }
-            Err(e) => {
-                eprintln!("Create failed: {}", e);
-                let response = (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse {
-                        message: format!("Create failed: {}", e),
-                        title: payload.title.clone(),
-                    }),
+    // チケット保存処理（列ごとに送られてきた順を並び順にする）
+    for list in &payload.projectData.lists {
+        let mut ids = Vec::with_capacity(list.tickets.len());
+        for ticket in &list.tickets {
+            if ticket.id == Some(0) {
+                // 新規チケット作成
+                let new_ticket = crate::entities::Ticket::create(
+                    title_id,
+                    user_ctx.user_id,
+                    list.category,
+                    ticket.content.clone(),
                 );
-                response
+                let saved =
+                    services::save_ticket(boards_repo, tickets_repo, hub, user_ctx, new_ticket)
+                        .await?;
+                ids.extend(saved.id);
+                continue;
             }
+
+            let Some(existing) = existing_tickets.iter().find(|t| t.id == ticket.id) else {
+                return Err(AppError::NotFound(format!(
+                    "Ticket {:?} not found",
+                    ticket.id
+                )));
+            };
+            ids.extend(existing.id);
+
+            // 変更のない既存チケットは更新しない（他人のチケットを含むため）
+            if existing.category == list.category && existing.content == ticket.content {
+                continue;
+            }
+
+            // 既存チケット更新
+            let mut updated_ticket = crate::entities::Ticket::new(
+                ticket.id,
+                title_id,
+                user_ctx.user_id,
+                list.category,
+                ticket.content.clone(),
+                chrono::Utc::now().naive_utc(),
+                chrono::Utc::now().naive_utc(),
+            );
+            updated_ticket.version = ticket.version.unwrap_or(existing.version);
+
+            services::update_ticket(boards_repo, tickets_repo, hub, user_ctx, updated_ticket)
+                .await?;
         }
+        services::reorder_tickets(boards_repo, tickets_repo, user_ctx, title_id, &ids).await?;
     }
+
+    let version = current_version(boards_repo, user_ctx, title_id).await?;
+    Ok((StatusCode::OK, "Board and tickets updated".into(), version))
+}
+
+async fn current_version(
+    boards_repo: &BoardsImpl,
+    user_ctx: &UserContext,
+    board_id: i64,
+) -> Result<i64, AppError> {
+    let board = services::get_board_by_id(boards_repo, user_ctx, board_id).await?;
+    Ok(board.version)
 }
 
 pub async fn get_board_data(
     user_ctx: UserContext,
     Path(title_id): Path<i64>,
+    Query(query): Query<BoardDataQuery>,
     State(repos): State<Arc<Repositories>>,
-) -> Result<Json<BoardTicketSummary>, StatusCode> {
+) -> Result<Json<BoardTicketSummary>, AppError> {
+    let data = load_board_data(&repos, &user_ctx, title_id, query.hide_authors).await?;
+    Ok(Json(data))
+}
+
+// hide_authors: 匿名で振り返る場合は作成者を返さない
+// （匿名設定のボードや匿名で書かれたチケットはオーナーにも作成者を返さない）
+async fn load_board_data(
+    repos: &Repositories,
+    user_ctx: &UserContext,
+    title_id: i64,
+    hide_authors: bool,
+) -> Result<BoardTicketSummary, AppError> {
     let boards_repo = &repos.boards;
     let tickets_repo = &repos.tickets;
 
     // Board取得
-    let board = match services::get_board_by_id(boards_repo, &user_ctx, title_id).await {
-        Ok(b) => b,
-        Err(e) => {
-            eprintln!("Error fetching board data: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
-    };
+    let board = services::get_board_by_id(boards_repo, user_ctx, title_id).await?;
 
     // チケット取得
-    let tickets = match services::get_all_tickets(tickets_repo, &user_ctx, title_id).await {
-        Ok(ts) => ts,
-        Err(e) => {
-            eprintln!("Error fetching tickets: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    let tickets = services::get_all_tickets(boards_repo, tickets_repo, user_ctx, title_id).await?;
+    let authors = if hide_authors || board.settings.anonymous {
+        None
+    } else {
+        Some(services::get_ticket_authors(&repos.accounts, &tickets).await?)
     };
 
-    // カテゴリ別にチケットを分類（Keep / Problem / Try）
-    let categories = vec!["Keep", "Problem", "Try"];
-    let lists: Vec<List> = categories
+    let votes = services::get_vote_tally(boards_repo, tickets_repo, user_ctx, title_id).await?;
+
+    // ボードの列ごとにチケットを分類
+    let columns = services::get_board_columns(boards_repo, user_ctx, title_id).await?;
+    let lists: Vec<List> = columns
         .into_iter()
+        .map(|column| column.category)
         .map(|cat| {
             let list_tickets = tickets
                 .iter()
@@ -267,68 +284,271 @@ pub async fn get_board_data(
                 .map(|t| Ticket {
                     id: t.id,
                     content: t.content.clone(),
+                    version: Some(t.version),
+                    author: authors
+                        .as_ref()
+                        .filter(|_| !t.is_author_concealed())
+                        .map(|names| TicketAuthor {
+                            id: t.author_id,
+                            display_name: names.get(&t.author_id).cloned(),
+                        }),
+                    author_token: t.author_token.clone(),
+                    votes: t.id.and_then(|id| votes.count(id)),
+                    my_votes: t.id.map(|id| votes.mine(id)),
                 })
                 .collect();
 
             List {
                 id: cat.to_string(),
-                category: cat.to_string(),
+                category: cat,
                 tickets: list_tickets,
             }
         })
         .collect();
 
     // 結果を組み立て
-    let response = BoardTicketSummary {
+    Ok(BoardTicketSummary {
         id: board.id.unwrap_or(0),
+        authorToken: board.author_token(user_ctx.user_id),
         title: board.title,
+        version: board.version,
+        settings: board.settings,
+        phase: board.phase,
+        remainingVotes: votes.remaining,
         projectData: ProjectData {
             id: board.id.map(|id| id.to_string()),
             lists,
         },
-    };
+    })
+}
+
+pub async fn update_settings(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+    Json(payload): Json<BoardSettingsPatch>,
+) -> Result<Json<BoardSettings>, AppError> {
+    // 指定された項目だけを現在の設定に上書きする
+    let board = services::get_board_by_id(&repos.boards, &user_ctx, board_id).await?;
+    let mut settings = board.settings;
+    if let Some(anonymous) = payload.anonymous {
+        settings.anonymous = anonymous;
+    }
+    if let Some(vote_budget) = payload.vote_budget {
+        settings.vote_budget = vote_budget;
+    }
+    if let Some(hide_votes) = payload.hide_votes {
+        settings.hide_votes = hide_votes;
+    }
+
+    let settings =
+        services::update_board_settings(&repos.boards, &hub, &user_ctx, board_id, settings).await?;
+    Ok(Json(settings))
+}
 
-    Ok(Json(response))
+pub async fn change_phase(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+    Json(payload): Json<PhasePayload>,
+) -> Result<Json<PhaseResponse>, AppError> {
+    let phase =
+        services::change_board_phase(&repos.boards, &hub, &user_ctx, board_id, payload.phase)
+            .await?;
+    Ok(Json(PhaseResponse { phase }))
 }
 
 pub async fn delete_board(
     user_ctx: UserContext,
     Path(title_id): Path<i64>,
     State(repos): State<Arc<Repositories>>,
-) -> Result<Response, StatusCode> {
-    let boards_repo = &repos.boards;
-    let tickets_repo = &repos.tickets;
+    State(hub): State<BoardHub>,
+) -> Result<Json<MessageResponse>, AppError> {
+    //ボード削除（チケットも合わせて削除される）
+    services::delete_board(&repos.boards, &repos.tickets, &hub, &user_ctx, title_id).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Board deleted successfully".into(),
+    }))
+}
+
+pub async fn list_members(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<Vec<MemberSummary>>, AppError> {
+    let members =
+        services::get_board_members(&repos.boards, &repos.accounts, &user_ctx, board_id).await?;
+    Ok(Json(members))
+}
+
+pub async fn add_member(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<MemberPayload>,
+) -> Result<Json<MemberSummary>, AppError> {
+    let member = services::add_board_member(
+        &repos.boards,
+        &repos.accounts,
+        &user_ctx,
+        board_id,
+        &payload.display_name,
+        payload.role,
+    )
+    .await?;
+    Ok(Json(member))
+}
 
-    // チケットを先に削除
+pub async fn remove_member(
+    user_ctx: UserContext,
+    Path((board_id, account_id)): Path<(i64, i64)>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Json<MessageResponse>, AppError> {
+    services::remove_board_member(&repos.boards, &hub, &user_ctx, board_id, account_id).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Member removed successfully".into(),
+    }))
+}
 
-    match services::get_all_tickets(tickets_repo, &user_ctx, title_id).await {
-        Ok(tickets) => {
-            for ticket in tickets {
-                if let Some(ticket_id) = ticket.id {
-                    if let Err(e) =
-                        services::delete_ticket(&repos.tickets, &user_ctx, ticket_id).await
-                    {
-                        eprintln!("Failed to delete ticket {}: {}", ticket_id, e);
+pub async fn create_ticket(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+    Json(payload): Json<TicketPayload>,
+) -> Result<(StatusCode, Json<crate::entities::Ticket>), AppError> {
+    let new_ticket = crate::entities::Ticket::create(
+        board_id,
+        user_ctx.user_id,
+        payload.category,
+        payload.content,
+    );
+
+    let ticket =
+        services::save_ticket(&repos.boards, &repos.tickets, &hub, &user_ctx, new_ticket).await?;
+    Ok((StatusCode::CREATED, Json(ticket)))
+}
+
+pub async fn list_actions(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<Vec<ActionItemSummary>>, AppError> {
+    let actions =
+        services::get_board_action_items(&repos.boards, &repos.action_items, &user_ctx, board_id)
+            .await?;
+    Ok(Json(
+        actions.into_iter().map(ActionItemSummary::from).collect(),
+    ))
+}
+
+pub async fn create_action(
+    user_ctx: UserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<ActionItemForm>,
+) -> Result<(StatusCode, Json<ActionItemSummary>), AppError> {
+    let action = services::create_action_item(
+        &repos.boards,
+        &repos.tickets,
+        &repos.action_items,
+        &user_ctx,
+        board_id,
+        &payload,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(ActionItemSummary::from(action))))
+}
+
+pub async fn update_action(
+    user_ctx: UserContext,
+    Path((board_id, action_id)): Path<(i64, i64)>,
+    State(repos): State<Arc<Repositories>>,
+    Json(payload): Json<ActionItemPatch>,
+) -> Result<Json<ActionItemSummary>, AppError> {
+    let action = services::update_action_item(
+        &repos.boards,
+        &repos.action_items,
+        &user_ctx,
+        board_id,
+        action_id,
+        &payload,
+    )
+    .await?;
+    Ok(Json(ActionItemSummary::from(action)))
+}
+
+pub async fn delete_action(
+    user_ctx: UserContext,
+    Path((board_id, action_id)): Path<(i64, i64)>,
+    State(repos): State<Arc<Repositories>>,
+) -> Result<Json<MessageResponse>, AppError> {
+    services::delete_action_item(
+        &repos.boards,
+        &repos.action_items,
+        &user_ctx,
+        board_id,
+        action_id,
+    )
+    .await?;
+
+    Ok(Json(MessageResponse {
+        message: "Action item deleted successfully".into(),
+    }))
+}
+
+pub async fn board_ws(
+    ws: WebSocketUpgrade,
+    WsUserContext(user_ctx): WsUserContext,
+    Path(board_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Response, AppError> {
+    services::get_board_by_id(&repos.boards, &user_ctx, board_id).await?;
+
+    let events = hub.subscribe(board_id);
+    Ok(ws.on_upgrade(move |socket| async move {
+        forward_board_events(socket, events, user_ctx.user_id).await;
+        hub.release(board_id);
+    }))
+}
+
+// ボードのイベントをクライアントへ中継（クライアントからの送信は切断検知のみ）
+// メンバーから外された・ボードが削除された場合は通知した上で接続を閉じる
+async fn forward_board_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<BoardEvent>,
+    account_id: i64,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                    if event.revokes_access(account_id) {
+                        let _ = socket.send(Message::Close(None)).await;
+                        break;
                     }
-                } else {
-                    eprintln!("チケットにIDが存在しないため削除できません: {:?}", ticket);
                 }
-            }
-        }
-        Err(e) => {
-            eprintln!("取得失敗: {}", e);
-        }
-    }
-
-    //ボード削除
-    match services::delete_board(boards_repo, &user_ctx, title_id).await {
-        Ok(_) => Ok(Json(MessageResponse {
-            message: "Board deleted successfully".into(),
-        })
-        .into_response()),
-        Err(e) => {
-            eprintln!("Error deleting board: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Board socket lagged behind by {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
         }
     }
 }
@@ -340,29 +560,98 @@ pub struct BoardSummary {
 }
 
 #[derive(Serialize)]
+#[allow(non_snake_case)]
 pub struct BoardTicketSummary {
     pub title: String,
     pub id: i64,
+    pub version: i64,
+    pub settings: BoardSettings,
+    pub phase: BoardPhase,
+    // 閲覧者が残りいくつ投票できるか
+    pub remainingVotes: i64,
+    // 閲覧者自身の作成者トークン（匿名のチケットが自分のものか判定するため）
+    pub authorToken: String,
     pub projectData: ProjectData,
 }
 
 #[derive(Deserialize)]
+#[allow(non_snake_case)]
 pub struct SavePayload {
     pub projectData: ProjectData,
     pub title: String,
     pub titleId: Option<String>,
+    // 新規作成時の列のテンプレート（省略時は KPT、更新時は無視）
+    #[serde(default)]
+    pub template: RetroTemplate,
+    // 読み込んだ時点のボードの版（省略時は競合を検出しない）
+    pub version: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Ticket {
     pub id: Option<i64>,
     pub content: String,
+    #[serde(default)]
+    pub version: Option<i64>,
+    // 読み込み時のみ返す（作成者を隠す場合は省略、保存時は無視）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<TicketAuthor>,
+    // 作成者が匿名の場合にだけ返す、ボードごとの不透明なトークン
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_token: Option<String>,
+    // 票数（読み込み時のみ、票数を隠している間は省略）と閲覧者自身の票数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub votes: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub my_votes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketAuthor {
+    pub id: i64,
+    // 削除済みのアカウントでは null
+    pub display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDataQuery {
+    #[serde(default)]
+    pub hide_authors: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSettingsPatch {
+    pub anonymous: Option<bool>,
+    pub vote_budget: Option<i32>,
+    pub hide_votes: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct PhasePayload {
+    // 省略時は次のフェーズ
+    #[serde(default)]
+    pub phase: Option<BoardPhase>,
+}
+
+#[derive(Serialize)]
+pub struct PhaseResponse {
+    pub phase: BoardPhase,
+}
+
+#[derive(Deserialize)]
+pub struct TicketPayload {
+    pub category: Category,
+    pub content: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct List {
     pub id: String,
-    pub category: String,
+    pub category: Category,
     pub tickets: Vec<Ticket>,
 }
 
@@ -376,15 +665,87 @@ pub struct ProjectData {
 struct ApiResponse {
     message: String,
     title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<i64>,
+}
+
+impl ApiResponse {
+    fn new(message: String, title: String) -> ApiResponse {
+        ApiResponse {
+            message,
+            title,
+            version: None,
+        }
+    }
 }
 
 #[derive(Serialize)]
-struct MessageResponse {
-    message: String,
+pub struct MessageResponse {
+    pub message: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ProjectDetail {
-    pub id: Option<String>,
-    pub lists: Vec<List>,
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberSummary {
+    pub account_id: i64,
+    pub display_name: String,
+    pub role: BoardRole,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberPayload {
+    pub display_name: String,
+    pub role: BoardRole,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItemForm {
+    pub ticket_id: i64,
+    // 省略時はチケットの内容
+    pub title: Option<String>,
+    pub assignee_id: i64,
+    pub due_date: chrono::NaiveDate,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItemPatch {
+    pub title: Option<String>,
+    pub assignee_id: Option<i64>,
+    pub due_date: Option<chrono::NaiveDate>,
+    pub status: Option<ActionStatus>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItemSummary {
+    pub id: i64,
+    pub board_id: i64,
+    pub ticket_id: i64,
+    pub title: String,
+    pub assignee_id: i64,
+    pub due_date: chrono::NaiveDate,
+    pub status: ActionStatus,
+    pub created_by: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ActionItem> for ActionItemSummary {
+    fn from(action: ActionItem) -> Self {
+        ActionItemSummary {
+            id: action.id.unwrap_or(0),
+            board_id: action.board_id,
+            ticket_id: action.ticket_id,
+            title: action.title,
+            assignee_id: action.assignee_id,
+            due_date: action.due_date,
+            status: action.status,
+            created_by: action.created_by,
+            created_at: action.created_at,
+            updated_at: action.updated_at,
+        }
+    }
 }
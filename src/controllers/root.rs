@@ -1,17 +1,40 @@
+use std::any::Any;
 use std::sync::Arc;
-use axum::{Router, routing::{options}};
+use std::time::Duration;
+use axum::{Router, response::{IntoResponse, Response}, routing::{options}};
+use crate::auth::AuthMode;
 use crate::database;
+use crate::error::AppError;
+use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::cors::CorsLayer;
 use axum::http::{HeaderValue, Method, header};
 use crate::controllers::accounts;
+use crate::controllers::actions;
 use crate::controllers::boards;
+use crate::controllers::tickets;
+use crate::events::BoardHub;
+use crate::notifier::LogNotifier;
+use crate::oidc::OidcClient;
+use crate::rate_limit::LoginLimiter;
+use crate::state::AppState;
 
 pub async fn app() -> Router {
-    let repos = Arc::new(database::establish_connection().await);
+    let (repos, sessions) = database::establish_connection().await;
+    database::spawn_session_cleanup(sessions.clone(), Duration::from_secs(60 * 60));
+    let login_limiter = LoginLimiter::from_env(&repos);
+    let state = AppState {
+        repos: Arc::new(repos),
+        sessions,
+        hub: BoardHub::default(),
+        notifier: Arc::new(LogNotifier),
+        login_limiter,
+        auth: AuthMode::from_env(),
+        oidc: OidcClient::from_env().await,
+    };
 
     let cors = CorsLayer::new()
         .allow_origin(HeaderValue::from_static("http://localhost:3000"))
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
         .allow_headers([
             header::CONTENT_TYPE,
             header::AUTHORIZATION,
@@ -21,7 +44,22 @@ pub async fn app() -> Router {
     Router::new()
         .route("/accounts/session", options(|| async {}))
         .route("/boards/list", options(|| async {}))
-        .nest("/accounts", accounts::accounts(repos.clone()))
-        .nest("/boards", boards::boards(repos.clone()))
+        .nest("/accounts", accounts::accounts(state.clone()))
+        .nest("/boards", boards::boards(state.clone()))
+        .nest("/actions", actions::actions(state.clone()))
+        .nest("/tickets", tickets::tickets(state))
+        // パニック時の 500 にも CORS ヘッダーが付くよう CORS を外側にする
+        .layer(CatchPanicLayer::custom(handle_panic))
         .layer(cors)
 }
+
+// ハンドラ内のパニックで接続を切らず、ログを残して 500 を返す
+fn handle_panic(payload: Box<dyn Any + Send + 'static>) -> Response {
+    let detail = payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "unknown panic".to_string());
+
+    AppError::Internal(format!("handler panicked: {}", detail)).into_response()
+}
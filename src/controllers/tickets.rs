@@ -0,0 +1,158 @@
+use super::boards::MessageResponse;
+use crate::database::Repositories;
+use crate::entities::{Category, Ticket};
+use crate::error::AppError;
+use crate::events::BoardHub;
+use crate::request::{Json, Path, UserContext};
+use crate::services;
+use crate::state::AppState;
+use axum::Router;
+use axum::extract::State;
+use axum::routing::{patch, post};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub fn tickets(state: AppState) -> Router {
+    Router::new()
+        .route("/:id", patch(update_ticket).delete(delete_ticket))
+        .route("/:id/move", post(move_ticket))
+        .route("/:id/votes", post(vote_ticket).delete(unvote_ticket))
+        .with_state(state)
+}
+
+pub async fn update_ticket(
+    user_ctx: UserContext,
+    Path(ticket_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+    Json(payload): Json<TicketPatch>,
+) -> Result<Json<Ticket>, AppError> {
+    let existing =
+        services::get_ticket(&repos.boards, &repos.tickets, &user_ctx, ticket_id).await?;
+
+    // 指定されなかった項目は現在の値のまま
+    let mut ticket = existing.clone();
+    ticket.update(
+        payload.category.unwrap_or(existing.category),
+        payload.content.unwrap_or(existing.content),
+    );
+    // 版の指定がなければ競合を検出しない
+    if let Some(version) = payload.version {
+        ticket.version = version;
+    }
+
+    // 競合時はサーバー側の最新状態が current として返る
+    let ticket =
+        services::update_ticket(&repos.boards, &repos.tickets, &hub, &user_ctx, ticket).await?;
+    Ok(Json(ticket))
+}
+
+pub async fn move_ticket(
+    user_ctx: UserContext,
+    Path(ticket_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+    Json(payload): Json<MovePayload>,
+) -> Result<Json<Ticket>, AppError> {
+    // チケットの更新と並び順の振り直しは同じトランザクションで行う
+    let tx = repos.begin().await?;
+    let boards_repo = repos.boards.join(&tx);
+    let tickets_repo = repos.tickets.join(&tx);
+    let events = hub.deferred();
+
+    let mut ticket =
+        services::get_ticket(&boards_repo, &tickets_repo, &user_ctx, ticket_id).await?;
+    ticket.category = payload.category;
+    // 版の指定がなければ競合を検出しない
+    if let Some(version) = payload.version {
+        ticket.version = version;
+    }
+
+    let ticket = services::move_ticket(
+        &boards_repo,
+        &tickets_repo,
+        &events,
+        &user_ctx,
+        ticket,
+        payload.index,
+    )
+    .await?;
+
+    tx.commit().await?;
+    events.flush();
+    Ok(Json(ticket))
+}
+
+pub async fn vote_ticket(
+    user_ctx: UserContext,
+    Path(ticket_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Json<VoteSummary>, AppError> {
+    let tally =
+        services::vote_ticket(&repos.boards, &repos.tickets, &hub, &user_ctx, ticket_id).await?;
+    Ok(Json(VoteSummary::new(ticket_id, &tally)))
+}
+
+pub async fn unvote_ticket(
+    user_ctx: UserContext,
+    Path(ticket_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Json<VoteSummary>, AppError> {
+    let tally =
+        services::unvote_ticket(&repos.boards, &repos.tickets, &hub, &user_ctx, ticket_id).await?;
+    Ok(Json(VoteSummary::new(ticket_id, &tally)))
+}
+
+pub async fn delete_ticket(
+    user_ctx: UserContext,
+    Path(ticket_id): Path<i64>,
+    State(repos): State<Arc<Repositories>>,
+    State(hub): State<BoardHub>,
+) -> Result<Json<MessageResponse>, AppError> {
+    services::delete_ticket(&repos.boards, &repos.tickets, &hub, &user_ctx, ticket_id).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Ticket deleted successfully".into(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct TicketPatch {
+    pub category: Option<Category>,
+    pub content: Option<String>,
+    // 読み込んだ時点のチケットの版
+    pub version: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct MovePayload {
+    pub category: Category,
+    // 移動先の列での位置（0始まり）
+    pub index: usize,
+    // 読み込んだ時点のチケットの版
+    pub version: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteSummary {
+    pub ticket_id: i64,
+    // 票数を隠している間は省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub votes: Option<i64>,
+    pub my_votes: i64,
+    pub remaining_votes: i64,
+}
+
+impl VoteSummary {
+    fn new(ticket_id: i64, tally: &services::VoteTally) -> VoteSummary {
+        VoteSummary {
+            ticket_id,
+            votes: tally.count(ticket_id),
+            my_votes: tally.mine(ticket_id),
+            remaining_votes: tally.remaining,
+        }
+    }
+}
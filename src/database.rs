@@ -1,10 +1,8 @@
 // use std::env;
 
-
 // use sqlx::MySqlPool;
 // use sqlx::mysql::MySqlPoolOptions;
 
-
 // use crate::repos_impl::{AccountsImpl, BoardsImpl, TicketsImpl};
 // use axum::extract::FromRef;
 
@@ -12,8 +10,6 @@
 
 // use async_sqlx_session::MySqlSessionStore;
 
-
-
 // #[derive(Clone)]
 // pub struct Repositories {
 //     pub accounts: AccountsImpl,
@@ -62,27 +58,142 @@
 // }
 
 // PostgreSQL接続
-use std::sync::Arc;
 use crate::constants::ENV_KEY_DATABASE_URL;
+use crate::error::AppError;
+use async_sqlx_session::PostgresSessionStore;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 pub type DbPool = Pool<PostgresConnectionManager<NoTls>>;
-use bb8::Pool;
+pub type DbConnection = PooledConnection<'static, PostgresConnectionManager<NoTls>>;
+use crate::repos_impl::{
+    AccountsImpl, ActionItemsImpl, ApiTokensImpl, BoardsImpl, IdentitiesImpl,
+    LoginAttemptsImpl, PasswordResetsImpl, RefreshTokensImpl, SessionsImpl, TicketsImpl,
+};
+use bb8::{Pool, PooledConnection};
 use bb8_postgres::PostgresConnectionManager;
-use tokio_postgres::NoTls;
-use crate::repos_impl::{AccountsImpl, BoardsImpl, TicketsImpl};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+use tokio_postgres::{Client, NoTls};
 
 #[derive(Clone)]
 pub struct Repositories {
+    pub pool: Arc<DbPool>,
     pub accounts: AccountsImpl,
+    pub action_items: ActionItemsImpl,
+    pub api_tokens: ApiTokensImpl,
     pub boards: BoardsImpl,
+    pub identities: IdentitiesImpl,
+    pub login_attempts: LoginAttemptsImpl,
+    pub password_resets: PasswordResetsImpl,
+    pub refresh_tokens: RefreshTokensImpl,
+    pub sessions: SessionsImpl,
     pub tickets: TicketsImpl,
 }
 
+impl Repositories {
+    // 各リポジトリの join() で参加させるトランザクションを開始
+    pub async fn begin(&self) -> Result<UnitOfWork, AppError> {
+        UnitOfWork::begin(&self.pool).await
+    }
+}
+
+// 複数のリポジトリで1つのトランザクションを共有する
+// commit() されずに破棄された場合はロールバックされる
+#[derive(Clone)]
+pub struct UnitOfWork {
+    conn: Arc<Mutex<TxConnection>>,
+}
+
+struct TxConnection(Option<DbConnection>);
+
+impl UnitOfWork {
+    pub async fn begin(pool: &DbPool) -> Result<UnitOfWork, AppError> {
+        let conn = pool.get_owned().await?;
+        conn.batch_execute("BEGIN").await?;
+
+        Ok(UnitOfWork {
+            conn: Arc::new(Mutex::new(TxConnection(Some(conn)))),
+        })
+    }
+
+    pub async fn commit(self) -> Result<(), AppError> {
+        self.finish("COMMIT").await
+    }
 
-pub async fn establish_connection() -> Repositories {
+    pub async fn rollback(self) -> Result<(), AppError> {
+        self.finish("ROLLBACK").await
+    }
+
+    async fn finish(self, statement: &str) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .await
+            .0
+            .take()
+            .ok_or_else(|| AppError::Database("Transaction already finished".to_string()))?;
+
+        conn.batch_execute(statement).await?;
+        Ok(())
+    }
+}
+
+impl Drop for TxConnection {
+    fn drop(&mut self) {
+        // 完了していないトランザクションのままプールへ戻さない
+        if let Some(conn) = self.0.take()
+            && let Ok(handle) = tokio::runtime::Handle::try_current()
+        {
+            handle.spawn(async move {
+                if let Err(e) = conn.batch_execute("ROLLBACK").await {
+                    eprintln!("Failed to roll back abandoned transaction: {}", e);
+                }
+            });
+        }
+    }
+}
+
+// プールから取得した接続、またはトランザクション中の共有接続
+pub enum DbClient<'a> {
+    Pooled(PooledConnection<'a, PostgresConnectionManager<NoTls>>),
+    Shared(MappedMutexGuard<'a, DbConnection>),
+}
+
+impl Deref for DbClient<'_> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        match self {
+            DbClient::Pooled(conn) => conn,
+            DbClient::Shared(conn) => conn,
+        }
+    }
+}
+
+// トランザクションに参加していればその接続を、なければプールから取得
+pub async fn acquire<'a>(
+    pool: &'a DbPool,
+    tx: Option<&'a UnitOfWork>,
+) -> Result<DbClient<'a>, AppError> {
+    match tx {
+        Some(tx) => MutexGuard::try_map(tx.conn.lock().await, |c| c.0.as_mut())
+            .map(DbClient::Shared)
+            .map_err(|_| AppError::Database("Transaction already finished".to_string())),
+        None => Ok(DbClient::Pooled(pool.get().await?)),
+    }
+}
+
+// リポジトリとセッションストアは起動時に一度だけ作成し、リクエスト間で共有する
+pub async fn establish_connection() -> (Repositories, PostgresSessionStore) {
     dotenv::dotenv().ok(); // .env 読み込み
 
     let database_url = std::env::var(ENV_KEY_DATABASE_URL).expect("DATABASE_URL must be set");
 
+    // セッションストア作成（内部で専用のプールを持つ）
+    let sessions = PostgresSessionStore::new(&database_url)
+        .await
+        .expect("Failed to create PostgresSessionStore");
+
     // PostgreSQL マネージャー作成
     let manager = PostgresConnectionManager::new_from_stringlike(database_url, NoTls)
         .expect("Failed to create Postgres manager");
@@ -95,9 +206,44 @@ pub async fn establish_connection() -> Repositories {
 
     let pool = Arc::new(pool); // Arc に包む（必要なら）
 
-    Repositories {
-        accounts: AccountsImpl { pool: pool.clone() },
-        boards: BoardsImpl { pool: pool.clone() },
-        tickets: TicketsImpl { pool },
-    }
+    let repos = Repositories {
+        pool: pool.clone(),
+        accounts: AccountsImpl {
+            pool: pool.clone(),
+            tx: None,
+        },
+        action_items: ActionItemsImpl { pool: pool.clone() },
+        api_tokens: ApiTokensImpl { pool: pool.clone() },
+        boards: BoardsImpl {
+            pool: pool.clone(),
+            tx: None,
+        },
+        identities: IdentitiesImpl {
+            pool: pool.clone(),
+            tx: None,
+        },
+        login_attempts: LoginAttemptsImpl { pool: pool.clone() },
+        password_resets: PasswordResetsImpl { pool: pool.clone() },
+        refresh_tokens: RefreshTokensImpl { pool: pool.clone() },
+        sessions: SessionsImpl {
+            pool: pool.clone(),
+            tx: None,
+        },
+        tickets: TicketsImpl { pool, tx: None },
+    };
+
+    (repos, sessions)
+}
+
+// 期限切れのセッションを定期的に削除する
+pub fn spawn_session_cleanup(store: PostgresSessionStore, period: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = store.cleanup().await {
+                tracing::error!("Failed to clean up expired sessions: {}", e);
+            }
+        }
+    });
 }
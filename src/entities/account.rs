@@ -1,9 +1,23 @@
 use argon2::{Argon2, password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, rand_core::OsRng, SaltString}};
 
+use chrono::{DateTime, Utc};
+
+use crate::crypto::random_token;
+use crate::error::{AppError, FieldError};
+
+// accounts.display_name は VARCHAR(16)
+pub const DISPLAY_NAME_MAX_CHARS: usize = 16;
+const PASSWORD_MIN_CHARS: usize = 8;
+const PASSWORD_MAX_CHARS: usize = 128;
+
 pub struct Account {
     id: Option<i64>,
     pub hashed_password: String,
     pub display_name: String,
+    // パスワードリセットを発行できる管理者
+    pub is_admin: bool,
+    // 削除済み（匿名化済み）のアカウント
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl Account {
@@ -12,28 +26,140 @@ impl Account {
             id,
             hashed_password,
             display_name,
+            is_admin: false,
+            deleted_at: None,
         }
     }
 
-    pub fn create( password: &str, display_name: &str) -> Account {
-        Account {
+    pub fn create( password: &str, display_name: &str) -> Result<Account, AppError> {
+        let fields: Vec<FieldError> = [
+            validate_display_name(display_name).map(|m| FieldError::new("display_name", m)),
+            validate_password(password).map(|m| FieldError::new("password", m)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !fields.is_empty() {
+            return Err(AppError::Validation {
+                message: "Invalid account".to_string(),
+                fields,
+            });
+        }
+
+        Ok(Account {
             id: None,
-            hashed_password: hash(password),
+            hashed_password: hash(password)?,
             display_name: display_name.to_string(),
+            is_admin: false,
+            deleted_at: None,
+        })
+    }
+
+    // 外部IdPで作成されるアカウント（推測できないランダムなパスワードを設定）
+    pub fn create_external(display_name: &str) -> Result<Account, AppError> {
+        if let Some(message) = validate_display_name(display_name) {
+            return Err(AppError::Validation {
+                message: "Invalid account".to_string(),
+                fields: vec![FieldError::new("display_name", message)],
+            });
         }
+
+        Ok(Account {
+            id: None,
+            hashed_password: hash(&random_token(32))?,
+            display_name: display_name.to_string(),
+            is_admin: false,
+            deleted_at: None,
+        })
     }
 
     pub fn id(&self) -> Option<i64> {
         self.id
     }
 
-    pub fn matches_password(&self, password: &str) -> bool {
-        let parsed_hash = PasswordHash::new(&self.hashed_password).unwrap();
-        Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok()
+    // 保存済みハッシュが壊れている場合はエラー（不一致とは区別する）
+    pub fn matches_password(&self, password: &str) -> Result<bool, AppError> {
+        let parsed_hash = PasswordHash::new(&self.hashed_password)
+            .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
+        Ok(Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok())
+    }
+
+    // 新しいパスワードを検証した上でハッシュを差し替える
+    pub fn change_password(&mut self, password: &str) -> Result<(), AppError> {
+        Account::validate_new_password(password)?;
+        self.hashed_password = hash(password)?;
+        Ok(())
+    }
+
+    pub fn change_display_name(&mut self, display_name: &str) -> Result<(), AppError> {
+        if let Some(message) = validate_display_name(display_name) {
+            return Err(AppError::Validation {
+                message: "Invalid display name".to_string(),
+                fields: vec![FieldError::new("display_name", message)],
+            });
+        }
+        self.display_name = display_name.to_string();
+        Ok(())
+    }
+
+    // 作成したボードやチケットの参照を残すため、行は消さずに個人情報を消す
+    // 表示名は登録では使えない "~" で始まる値にし、パスワードは推測できない値にする
+    pub fn anonymize(&mut self) -> Result<(), AppError> {
+        self.display_name = format!("~{}", random_token(7));
+        self.hashed_password = hash(&random_token(32))?;
+        self.is_admin = false;
+        self.deleted_at = Some(Utc::now());
+        Ok(())
+    }
+
+    pub fn validate_new_password(password: &str) -> Result<(), AppError> {
+        match validate_password(password) {
+            Some(message) => Err(AppError::Validation {
+                message: "Invalid password".to_string(),
+                fields: vec![FieldError::new("new_password", message)],
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// 英数字（全角を含む）と _ - . のみ
+fn validate_display_name(display_name: &str) -> Option<&'static str> {
+    let len = display_name.chars().count();
+    if len == 0 {
+        Some("Display name is required")
+    } else if len > DISPLAY_NAME_MAX_CHARS {
+        Some("Display name must be at most 16 characters")
+    } else if !display_name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        Some("Display name may only contain letters, digits, '_', '-' and '.'")
+    } else {
+        None
     }
 }
 
-fn hash(str: &str) -> String {
+// 英字と数字をそれぞれ1文字以上含む
+fn validate_password(password: &str) -> Option<&'static str> {
+    let len = password.chars().count();
+    if len < PASSWORD_MIN_CHARS {
+        Some("Password must be at least 8 characters")
+    } else if len > PASSWORD_MAX_CHARS {
+        Some("Password must be at most 128 characters")
+    } else if !password.chars().any(|c| c.is_alphabetic())
+        || !password.chars().any(|c| c.is_numeric())
+    {
+        Some("Password must contain both letters and digits")
+    } else {
+        None
+    }
+}
+
+fn hash(str: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
-    Argon2::default().hash_password(str.as_bytes(), &salt).unwrap().to_string()
-}
\ No newline at end of file
+    Argon2::default()
+        .hash_password(str.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))
+}
@@ -0,0 +1,104 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Category, Ticket};
+use crate::error::{AppError, FieldError};
+
+const TITLE_MAX_CHARS: usize = 255;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionStatus {
+    Open,
+    Done,
+}
+
+impl ActionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionStatus::Open => "open",
+            ActionStatus::Done => "done",
+        }
+    }
+}
+
+impl FromStr for ActionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(ActionStatus::Open),
+            "done" => Ok(ActionStatus::Done),
+            _ => Err(format!("Unknown action status: {}", s)),
+        }
+    }
+}
+
+// Try のチケットから作るアクションアイテム（担当者と期限を持つ）
+#[derive(Debug, Clone)]
+pub struct ActionItem {
+    pub id: Option<i64>,
+    pub board_id: i64,
+    pub ticket_id: i64,
+    pub title: String,
+    pub assignee_id: i64,
+    pub due_date: NaiveDate,
+    pub status: ActionStatus,
+    pub created_by: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ActionItem {
+    // 新規作成用（title 省略時はチケットの内容を使う）
+    pub fn create(
+        ticket: &Ticket,
+        created_by: i64,
+        title: Option<String>,
+        assignee_id: i64,
+        due_date: NaiveDate,
+    ) -> Result<ActionItem, AppError> {
+        if ticket.category != Category::Try {
+            return Err(AppError::validation(
+                "Action items can only be created from Try tickets",
+            ));
+        }
+        let ticket_id = ticket
+            .id
+            .ok_or_else(|| AppError::validation("Ticket ID is not set"))?;
+
+        let now = Utc::now();
+        let mut item = ActionItem {
+            id: None,
+            board_id: ticket.board_id,
+            ticket_id,
+            title: String::new(),
+            assignee_id,
+            due_date,
+            status: ActionStatus::Open,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        item.change_title(title.unwrap_or_else(|| ticket.content.clone()))?;
+        Ok(item)
+    }
+
+    pub fn change_title(&mut self, title: String) -> Result<(), AppError> {
+        let title = title.trim();
+        if title.is_empty() || title.chars().count() > TITLE_MAX_CHARS {
+            return Err(AppError::Validation {
+                message: "Invalid action item".to_string(),
+                fields: vec![FieldError::new(
+                    "title",
+                    format!("Title must be between 1 and {} characters", TITLE_MAX_CHARS),
+                )],
+            });
+        }
+        self.title = title.to_string();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{random_token, sha256_hex};
+use crate::error::{AppError, FieldError};
+
+// Bearer トークンがセッションIDやJWTでなく個人用トークンであることを示す
+pub const API_TOKEN_PREFIX: &str = "kpt_";
+const NAME_MAX_CHARS: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiScope {
+    #[serde(rename = "boards:read")]
+    BoardsRead,
+    // boards:read を含む
+    #[serde(rename = "boards:write")]
+    BoardsWrite,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 2] = [ApiScope::BoardsRead, ApiScope::BoardsWrite];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::BoardsRead => "boards:read",
+            ApiScope::BoardsWrite => "boards:write",
+        }
+    }
+
+    // このスコープを持つトークンで required の操作ができるか
+    pub fn grants(&self, required: ApiScope) -> bool {
+        *self == required || (*self == ApiScope::BoardsWrite && required == ApiScope::BoardsRead)
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "boards:read" => Ok(ApiScope::BoardsRead),
+            "boards:write" => Ok(ApiScope::BoardsWrite),
+            _ => Err(format!("Unknown API token scope: {}", s)),
+        }
+    }
+}
+
+// スクリプトやCIから使う個人用アクセストークン（DBにはハッシュのみ保存）
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    pub id: Option<i64>,
+    pub account_id: i64,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    // 平文のトークンは発行時に一度だけ返す
+    // scopes が None ならすべてのスコープ、expires_at が None なら無期限
+    pub fn issue(
+        account_id: i64,
+        name: &str,
+        scopes: Option<Vec<ApiScope>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(String, ApiToken), AppError> {
+        let name = name.trim();
+        let mut scopes = scopes.unwrap_or_else(|| ApiScope::ALL.to_vec());
+        let mut seen = vec![];
+        scopes.retain(|scope| {
+            let first = !seen.contains(scope);
+            seen.push(*scope);
+            first
+        });
+
+        let mut fields = vec![];
+        if name.is_empty() || name.chars().count() > NAME_MAX_CHARS {
+            fields.push(FieldError::new(
+                "name",
+                "Name must be between 1 and 64 characters",
+            ));
+        }
+        if scopes.is_empty() {
+            fields.push(FieldError::new("scopes", "At least one scope is required"));
+        }
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            fields.push(FieldError::new(
+                "expires_at",
+                "Expiry must be in the future",
+            ));
+        }
+        if !fields.is_empty() {
+            return Err(AppError::Validation {
+                message: "Invalid API token".to_string(),
+                fields,
+            });
+        }
+
+        let token = format!("{}{}", API_TOKEN_PREFIX, random_token(32));
+        let entity = ApiToken {
+            id: None,
+            account_id,
+            name: name.to_string(),
+            token_hash: ApiToken::hash(&token),
+            scopes,
+            expires_at,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        Ok((token, entity))
+    }
+
+    pub fn hash(token: &str) -> String {
+        sha256_hex(token)
+    }
+}
@@ -1,5 +1,105 @@
+use std::str::FromStr;
+
 use chrono::{NaiveDateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{random_token, sha256_hex};
+use crate::error::{AppError, FieldError};
+
+const DEFAULT_VOTE_BUDGET: i32 = 3;
+const VOTE_BUDGET_MAX: i32 = 20;
+
+// オーナーが変更できるボードの設定
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSettings {
+    // 匿名モード：チケットの作成者を誰にも返さない
+    pub anonymous: bool,
+    // 1人あたりの投票数（ボード全体で）
+    pub vote_budget: i32,
+    // 投票フェーズが終わるまで票数を返さない（自分の票は返す）
+    pub hide_votes: bool,
+}
+
+impl Default for BoardSettings {
+    fn default() -> BoardSettings {
+        BoardSettings {
+            anonymous: false,
+            vote_budget: DEFAULT_VOTE_BUDGET,
+            hide_votes: false,
+        }
+    }
+}
+
+impl BoardSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(0..=VOTE_BUDGET_MAX).contains(&self.vote_budget) {
+            return Err(AppError::Validation {
+                message: "Invalid board settings".to_string(),
+                fields: vec![FieldError::new(
+                    "voteBudget",
+                    format!("Vote budget must be between 0 and {}", VOTE_BUDGET_MAX),
+                )],
+            });
+        }
+        Ok(())
+    }
+}
+
+// 振り返りの進行フェーズ（write → group → vote → discuss → close）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardPhase {
+    #[default]
+    Write,
+    Group,
+    Vote,
+    Discuss,
+    Close,
+}
+
+impl BoardPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardPhase::Write => "write",
+            BoardPhase::Group => "group",
+            BoardPhase::Vote => "vote",
+            BoardPhase::Discuss => "discuss",
+            BoardPhase::Close => "close",
+        }
+    }
+
+    // close の次はない
+    pub fn next(&self) -> Option<BoardPhase> {
+        match self {
+            BoardPhase::Write => Some(BoardPhase::Group),
+            BoardPhase::Group => Some(BoardPhase::Vote),
+            BoardPhase::Vote => Some(BoardPhase::Discuss),
+            BoardPhase::Discuss => Some(BoardPhase::Close),
+            BoardPhase::Close => None,
+        }
+    }
+
+    // 1つ先へ進めるか、やり直しのため1つ前へ戻せる（close からは戻せない）
+    pub fn can_transition_to(&self, to: BoardPhase) -> bool {
+        self.next() == Some(to) || (*self != BoardPhase::Close && to.next() == Some(*self))
+    }
+}
+
+impl FromStr for BoardPhase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "write" => Ok(BoardPhase::Write),
+            "group" => Ok(BoardPhase::Group),
+            "vote" => Ok(BoardPhase::Vote),
+            "discuss" => Ok(BoardPhase::Discuss),
+            "close" => Ok(BoardPhase::Close),
+            _ => Err(format!("Unknown board phase: {}", s)),
+        }
+    }
+}
 
 #[derive(Serialize, Debug, Clone)]
 pub struct Board {
@@ -8,6 +108,12 @@ pub struct Board {
     pub created_by: i64,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub version: i64,
+    pub settings: BoardSettings,
+    pub phase: BoardPhase,
+    // 作成者トークンの導出に使うボードごとの秘密値
+    #[serde(skip)]
+    pub author_salt: String,
     deleted: bool,
 }
 
@@ -26,6 +132,10 @@ impl Board {
             created_by,
             created_at,
             updated_at,
+            version: 1,
+            settings: BoardSettings::default(),
+            phase: BoardPhase::default(),
+            author_salt: String::new(),
             deleted: false,
         }
     }
@@ -39,10 +149,30 @@ impl Board {
             created_by,
             created_at: now,
             updated_at: now,
+            version: 1,
+            settings: BoardSettings::default(),
+            phase: BoardPhase::default(),
+            author_salt: random_token(16),
             deleted: false,
         }
     }
 
+    // 匿名のチケットで作成者IDの代わりに返す値
+    // 同じボード・同じ作成者なら同じ値になり、クライアントは自分のチケットか判定できる
+    pub fn author_token(&self, account_id: i64) -> String {
+        sha256_hex(&format!("{}:{}", self.author_salt, account_id))[..16].to_string()
+    }
+
+    // 投票フェーズが終わるまでは設定により票数を隠す
+    pub fn votes_visible(&self) -> bool {
+        !self.settings.hide_votes || self.phase > BoardPhase::Vote
+    }
+
+    // close 後はボード・チケットを変更できない
+    pub fn is_closed(&self) -> bool {
+        self.phase == BoardPhase::Close
+    }
+
     // 更新（タイトル変更など）
     pub fn update(&mut self, new_title: String) {
         self.title = new_title;
@@ -66,3 +196,51 @@ impl Board {
         &self.title
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHASES: [BoardPhase; 5] = [
+        BoardPhase::Write,
+        BoardPhase::Group,
+        BoardPhase::Vote,
+        BoardPhase::Discuss,
+        BoardPhase::Close,
+    ];
+
+    #[test]
+    fn phases_advance_in_order() {
+        for pair in PHASES.windows(2) {
+            assert_eq!(pair[0].next(), Some(pair[1]));
+            assert!(pair[0].can_transition_to(pair[1]));
+        }
+        assert_eq!(BoardPhase::Close.next(), None);
+    }
+
+    #[test]
+    fn phases_can_step_back_until_closed() {
+        assert!(BoardPhase::Vote.can_transition_to(BoardPhase::Group));
+        assert!(BoardPhase::Discuss.can_transition_to(BoardPhase::Vote));
+        assert!(!BoardPhase::Close.can_transition_to(BoardPhase::Discuss));
+    }
+
+    #[test]
+    fn phases_cannot_be_skipped() {
+        assert!(!BoardPhase::Write.can_transition_to(BoardPhase::Vote));
+        assert!(!BoardPhase::Group.can_transition_to(BoardPhase::Close));
+        for phase in PHASES {
+            assert!(!phase.can_transition_to(phase));
+        }
+    }
+
+    #[test]
+    fn hidden_votes_are_shown_after_vote_phase() {
+        let mut board = Board::create("retro".to_string(), 1);
+        board.settings.hide_votes = true;
+        board.phase = BoardPhase::Vote;
+        assert!(!board.votes_visible());
+        board.phase = BoardPhase::Discuss;
+        assert!(board.votes_visible());
+    }
+}
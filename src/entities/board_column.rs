@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::Category;
+
+// ボードの列（チケットの分類）。position の昇順に表示する
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BoardColumn {
+    pub board_id: i64,
+    pub category: Category,
+    pub position: i32,
+}
+
+impl BoardColumn {
+    pub fn new(board_id: i64, category: Category, position: i32) -> BoardColumn {
+        BoardColumn {
+            board_id,
+            category,
+            position,
+        }
+    }
+
+    // テンプレートの列をその順番で作成
+    pub fn from_template(board_id: i64, template: RetroTemplate) -> Vec<BoardColumn> {
+        template
+            .categories()
+            .iter()
+            .zip(0..)
+            .map(|(category, position)| BoardColumn::new(board_id, *category, position))
+            .collect()
+    }
+}
+
+// ボード作成時に選べる振り返りの形式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RetroTemplate {
+    #[default]
+    Kpt,
+    StartStopContinue,
+    FourLs,
+    MadSadGlad,
+}
+
+impl RetroTemplate {
+    pub fn categories(&self) -> &'static [Category] {
+        match self {
+            RetroTemplate::Kpt => &[Category::Keep, Category::Problem, Category::Try],
+            RetroTemplate::StartStopContinue => {
+                &[Category::Start, Category::Stop, Category::Continue]
+            }
+            RetroTemplate::FourLs => &[
+                Category::Liked,
+                Category::Learned,
+                Category::Lacked,
+                Category::LongedFor,
+            ],
+            RetroTemplate::MadSadGlad => &[Category::Mad, Category::Sad, Category::Glad],
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardRole {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl BoardRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardRole::Owner => "owner",
+            BoardRole::Editor => "editor",
+            BoardRole::Viewer => "viewer",
+        }
+    }
+}
+
+impl FromStr for BoardRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(BoardRole::Owner),
+            "editor" => Ok(BoardRole::Editor),
+            "viewer" => Ok(BoardRole::Viewer),
+            _ => Err(format!("Unknown board role: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BoardMember {
+    pub board_id: i64,
+    pub account_id: i64,
+    pub role: BoardRole,
+    pub created_at: NaiveDateTime,
+}
+
+impl BoardMember {
+    // DBなどからの読み込み時
+    pub fn new(
+        board_id: i64,
+        account_id: i64,
+        role: BoardRole,
+        created_at: NaiveDateTime,
+    ) -> BoardMember {
+        BoardMember {
+            board_id,
+            account_id,
+            role,
+            created_at,
+        }
+    }
+
+    // 新規追加用
+    pub fn create(board_id: i64, account_id: i64, role: BoardRole) -> BoardMember {
+        BoardMember {
+            board_id,
+            account_id,
+            role,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::types::{FromSql, IsNull, ToSql, Type, accepts, to_sql_checked};
+
+// チケットの分類（DBにはこの名前のまま TEXT で保存する）
+// ボードで使える分類は作成時のテンプレートで決まる（RetroTemplate）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    // KPT
+    Keep,
+    Problem,
+    Try,
+    // Start / Stop / Continue
+    Start,
+    Stop,
+    Continue,
+    // 4Ls
+    Liked,
+    Learned,
+    Lacked,
+    #[serde(rename = "Longed for")]
+    LongedFor,
+    // Mad / Sad / Glad
+    Mad,
+    Sad,
+    Glad,
+}
+
+impl Category {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Keep => "Keep",
+            Category::Problem => "Problem",
+            Category::Try => "Try",
+            Category::Start => "Start",
+            Category::Stop => "Stop",
+            Category::Continue => "Continue",
+            Category::Liked => "Liked",
+            Category::Learned => "Learned",
+            Category::Lacked => "Lacked",
+            Category::LongedFor => "Longed for",
+            Category::Mad => "Mad",
+            Category::Sad => "Sad",
+            Category::Glad => "Glad",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Keep" => Ok(Category::Keep),
+            "Problem" => Ok(Category::Problem),
+            "Try" => Ok(Category::Try),
+            "Start" => Ok(Category::Start),
+            "Stop" => Ok(Category::Stop),
+            "Continue" => Ok(Category::Continue),
+            "Liked" => Ok(Category::Liked),
+            "Learned" => Ok(Category::Learned),
+            "Lacked" => Ok(Category::Lacked),
+            "Longed for" => Ok(Category::LongedFor),
+            "Mad" => Ok(Category::Mad),
+            "Sad" => Ok(Category::Sad),
+            "Glad" => Ok(Category::Glad),
+            _ => Err(format!("Unknown category: {}", s)),
+        }
+    }
+}
+
+impl ToSql for Category {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.as_str().to_sql(ty, out)
+    }
+
+    accepts!(TEXT, VARCHAR);
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Category {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let value = <&str as FromSql>::from_sql(ty, raw)?;
+        Ok(value.parse()?)
+    }
+
+    accepts!(TEXT, VARCHAR);
+}
@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+// セッションストアに保存されたログインセッション
+// id はストア上のID（Bearerトークンそのものではない）
+#[derive(Debug, Clone)]
+pub struct LoginSession {
+    pub id: String,
+    pub account_id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub user_agent: Option<String>,
+}
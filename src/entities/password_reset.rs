@@ -0,0 +1,30 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::crypto::{random_token, sha256_hex};
+
+// パスワードリセット用の使い捨てトークン
+// DBにはトークンのハッシュのみ保存する
+#[derive(Debug, Clone)]
+pub struct PasswordResetToken {
+    pub token_hash: String,
+    pub account_id: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    // 通知に載せる生のトークンと保存用のエンティティを返す
+    pub fn issue(account_id: i64, ttl: Duration) -> (String, PasswordResetToken) {
+        let token = random_token(32);
+
+        let entity = PasswordResetToken {
+            token_hash: PasswordResetToken::hash(&token),
+            account_id,
+            expires_at: Utc::now() + ttl,
+        };
+        (token, entity)
+    }
+
+    pub fn hash(token: &str) -> String {
+        sha256_hex(token)
+    }
+}
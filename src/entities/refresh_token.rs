@@ -0,0 +1,38 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::crypto::{random_token, sha256_hex};
+
+// JWTモードのリフレッシュトークン
+// 使うたびに同じ family の新しいトークンへ差し替え、使用済みの再利用を検出する
+#[derive(Debug, Clone)]
+pub struct RefreshToken {
+    pub token_hash: String,
+    pub family_id: String,
+    pub account_id: i64,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl RefreshToken {
+    // family_id が None ならログインによる新しい系列
+    pub fn issue(
+        account_id: i64,
+        family_id: Option<String>,
+        ttl: Duration,
+    ) -> (String, RefreshToken) {
+        let token = random_token(32);
+
+        let entity = RefreshToken {
+            token_hash: RefreshToken::hash(&token),
+            family_id: family_id.unwrap_or_else(|| random_token(16)),
+            account_id,
+            expires_at: Utc::now() + ttl,
+            used_at: None,
+        };
+        (token, entity)
+    }
+
+    pub fn hash(token: &str) -> String {
+        sha256_hex(token)
+    }
+}
@@ -1,15 +1,27 @@
 use chrono::{NaiveDateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::{Board, Category};
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Ticket {
     pub id: Option<i64>,
     pub board_id: i64,
     pub author_id: i64,
-    pub category: String,
+    pub category: Category,
     pub content: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub version: i64,
+    // 列の中での並び順（レスポンスには含めず、並びそのもので返す）
+    #[serde(default)]
+    pub position: i32,
+    // 匿名モードのボードで書かれたチケット（後で匿名モードを解除しても作成者を返さない）
+    #[serde(default)]
+    pub anonymous: bool,
+    // 設定されていればシリアライズ時に author_id の代わりに返す
+    #[serde(default)]
+    pub author_token: Option<String>,
     deleted: bool,
 }
 
@@ -18,7 +30,7 @@ impl Ticket {
         id: Option<i64>,
         board_id: i64,
         author_id: i64,
-        category: String,
+        category: Category,
         content: String,
         created_at: NaiveDateTime,
         updated_at: NaiveDateTime,
@@ -31,33 +43,47 @@ impl Ticket {
             content,
             created_at,
             updated_at,
+            version: 1,
+            position: 0,
+            anonymous: false,
+            author_token: None,
             deleted: false,
         }
     }
-    pub fn create(
-        board_id: i64,
-        author_id: i64,
-        category: String,
-        content: String,
-    ) -> Ticket {
+    pub fn create(board_id: i64, author_id: i64, category: Category, content: String) -> Ticket {
         Ticket {
-            id:None,
+            id: None,
             board_id,
             author_id,
             category,
             content,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            version: 1,
+            position: 0,
+            anonymous: false,
+            author_token: None,
             deleted: false,
         }
     }
 
-    pub fn update(&mut self, new_category: String, new_content: String) {
+    pub fn update(&mut self, new_category: Category, new_content: String) {
         self.category = new_category;
         self.content = new_content;
         self.updated_at = Utc::now().naive_utc();
     }
 
+    // 匿名のチケットであれば作成者IDを隠す（ボードから返すチケットはすべてこれを通す）
+    pub fn conceal_author(&mut self, board: &Board) {
+        if self.anonymous || board.settings.anonymous {
+            self.author_token = Some(board.author_token(self.author_id));
+        }
+    }
+
+    pub fn is_author_concealed(&self) -> bool {
+        self.author_token.is_some()
+    }
+
     pub fn id(&self) -> Option<i64> {
         self.id
     }
@@ -70,3 +96,72 @@ impl Ticket {
         self.deleted = true;
     }
 }
+
+// author_token があるチケットは author_id を含めない
+impl Serialize for Ticket {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct TicketJson<'a> {
+            id: Option<i64>,
+            board_id: i64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            author_id: Option<i64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            author_token: Option<&'a str>,
+            category: Category,
+            content: &'a str,
+            created_at: NaiveDateTime,
+            updated_at: NaiveDateTime,
+            version: i64,
+            deleted: bool,
+        }
+
+        TicketJson {
+            id: self.id,
+            board_id: self.board_id,
+            author_id: (!self.is_author_concealed()).then_some(self.author_id),
+            author_token: self.author_token.as_deref(),
+            category: self.category,
+            content: &self.content,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            version: self.version,
+            deleted: self.deleted,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket_on(board: &Board) -> Ticket {
+        Ticket::create(board.id.unwrap(), 7, Category::Keep, "note".to_string())
+    }
+
+    #[test]
+    fn author_id_is_serialized_when_not_concealed() {
+        let mut board = Board::create("retro".to_string(), 1);
+        board.id = Some(1);
+        let mut ticket = ticket_on(&board);
+        ticket.conceal_author(&board);
+
+        let json = serde_json::to_value(&ticket).unwrap();
+        assert_eq!(json["author_id"], 7);
+        assert!(json.get("author_token").is_none());
+    }
+
+    #[test]
+    fn author_id_is_omitted_when_concealed() {
+        let mut board = Board::create("retro".to_string(), 1);
+        board.id = Some(1);
+        board.settings.anonymous = true;
+        let mut ticket = ticket_on(&board);
+        ticket.conceal_author(&board);
+
+        let json = serde_json::to_value(&ticket).unwrap();
+        assert!(json.get("author_id").is_none());
+        assert_eq!(json["author_token"], board.author_token(7));
+    }
+}
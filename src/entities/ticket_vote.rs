@@ -0,0 +1,30 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+
+// チケットへの1票（同じチケットに複数票を入れられる）
+#[derive(Serialize, Debug, Clone)]
+pub struct TicketVote {
+    pub ticket_id: i64,
+    pub account_id: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl TicketVote {
+    // DBなどからの読み込み時
+    pub fn new(ticket_id: i64, account_id: i64, created_at: NaiveDateTime) -> TicketVote {
+        TicketVote {
+            ticket_id,
+            account_id,
+            created_at,
+        }
+    }
+
+    // 新規投票用
+    pub fn create(ticket_id: i64, account_id: i64) -> TicketVote {
+        TicketVote {
+            ticket_id,
+            account_id,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
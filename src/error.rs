@@ -0,0 +1,197 @@
+use axum::Json;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> FieldError {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    Forbidden(String),
+    // current: クライアントがマージに使うサーバー側の最新状態
+    Conflict {
+        message: String,
+        current: Option<serde_json::Value>,
+    },
+    Validation {
+        message: String,
+        fields: Vec<FieldError>,
+    },
+    // retry_after: Retry-After ヘッダーで返す待ち時間
+    TooManyRequests {
+        message: String,
+        retry_after: std::time::Duration,
+    },
+    Database(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn conflict(message: impl Into<String>) -> AppError {
+        AppError::Conflict {
+            message: message.into(),
+            current: None,
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> AppError {
+        AppError::Validation {
+            message: message.into(),
+            fields: vec![],
+        }
+    }
+
+    // 競合時に最新状態を添える
+    pub fn with_current(self, value: impl Serialize) -> AppError {
+        match self {
+            AppError::Conflict { message, .. } => AppError::Conflict {
+                message,
+                current: serde_json::to_value(value).ok(),
+            },
+            other => other,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::NotFound(_) => "not_found",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Conflict { .. } => "conflict",
+            AppError::Validation { .. } => "validation",
+            AppError::TooManyRequests { .. } => "too_many_requests",
+            AppError::Database(_) => "database",
+            AppError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::BadRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::NotFound(message)
+            | AppError::Forbidden(message)
+            | AppError::Conflict { message, .. }
+            | AppError::Validation { message, .. }
+            | AppError::TooManyRequests { message, .. }
+            | AppError::Database(message)
+            | AppError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+// エラーボディは { "error": 種別, "message": 内容, ... } で統一
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+
+        if let AppError::TooManyRequests {
+            message,
+            retry_after,
+        } = self
+        {
+            // 端数は切り上げて秒で返す
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            let body = json!({ "error": code, "message": message, "retryAfter": secs });
+            return (
+                status,
+                [(header::RETRY_AFTER, secs.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+
+        let body = match self {
+            AppError::Conflict { message, current } => {
+                json!({ "error": code, "message": message, "current": current })
+            }
+            AppError::Validation { message, fields } => {
+                json!({ "error": code, "message": message, "fields": fields })
+            }
+            AppError::Database(message) => {
+                // DBの詳細はクライアントに返さずログにのみ出す
+                tracing::error!("database error: {}", message);
+                json!({ "error": code, "message": "Internal server error" })
+            }
+            AppError::Internal(message) => {
+                tracing::error!("internal error: {}", message);
+                json!({ "error": code, "message": "Internal server error" })
+            }
+            other => json!({ "error": code, "message": other.to_string() }),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+impl From<tokio_postgres::Error> for AppError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<bb8::RunError<tokio_postgres::Error>> for AppError {
+    fn from(e: bb8::RunError<tokio_postgres::Error>) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+// エクストラクタの拒否もエラーボディの形式をそろえる（request.rs の Json・Path・Query）
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            // 形式は正しいが型が合わない
+            JsonRejection::JsonDataError(e) => AppError::validation(e.body_text()),
+            other => AppError::BadRequest(other.body_text()),
+        }
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::entities::{BoardPhase, BoardSettings, Category, Ticket};
+
+// 1ボードあたりの未配信イベントの上限（超えた購読者は古いイベントを取りこぼす）
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[allow(clippy::enum_variant_names)]
+pub enum BoardEvent {
+    #[serde(rename_all = "camelCase")]
+    TicketCreated { ticket: Ticket },
+    #[serde(rename_all = "camelCase")]
+    TicketUpdated { ticket: Ticket },
+    #[serde(rename_all = "camelCase")]
+    TicketDeleted { ticket_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    TicketMoved {
+        ticket: Ticket,
+        from_category: Category,
+        // 移動先の列での位置（省略時は列の末尾）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    // 票数を隠している間は votes を省略する
+    #[serde(rename_all = "camelCase")]
+    VotesUpdated {
+        ticket_id: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        votes: Option<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SettingsUpdated { settings: BoardSettings },
+    #[serde(rename_all = "camelCase")]
+    PhaseChanged { phase: BoardPhase },
+    // 以下はボードを閲覧できなくなったことの通知（該当する接続は通知後に閉じる）
+    #[serde(rename_all = "camelCase")]
+    MemberRemoved { account_id: i64 },
+    BoardDeleted,
+}
+
+impl BoardEvent {
+    // このイベントで account_id がボードを閲覧できなくなるか
+    pub fn revokes_access(&self, account_id: i64) -> bool {
+        match self {
+            BoardEvent::MemberRemoved { account_id: removed } => *removed == account_id,
+            BoardEvent::BoardDeleted => true,
+            _ => false,
+        }
+    }
+}
+
+type PendingEvents = Arc<Mutex<Vec<(i64, BoardEvent)>>>;
+
+// ボードごとのイベント配信ハブ（プロセス内）
+#[derive(Clone, Default)]
+pub struct BoardHub {
+    channels: Arc<Mutex<HashMap<i64, broadcast::Sender<BoardEvent>>>>,
+    pending: Option<PendingEvents>,
+}
+
+impl BoardHub {
+    // flush() まで配信を保留するハブ（トランザクションのコミット後に配信する用途）
+    pub fn deferred(&self) -> BoardHub {
+        BoardHub {
+            channels: self.channels.clone(),
+            pending: Some(Arc::default()),
+        }
+    }
+
+    pub fn flush(&self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        let events: Vec<_> = pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+        for (board_id, event) in events {
+            self.send(board_id, event);
+        }
+    }
+
+    pub fn subscribe(&self, board_id: i64) -> broadcast::Receiver<BoardEvent> {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels
+            .entry(board_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    pub fn publish(&self, board_id: i64, event: BoardEvent) {
+        match &self.pending {
+            Some(pending) => pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((board_id, event)),
+            None => self.send(board_id, event),
+        }
+    }
+
+    fn send(&self, board_id: i64, event: BoardEvent) {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = channels.get(&board_id)
+            && sender.send(event).is_err()
+        {
+            // 購読者がいなくなったチャンネルは破棄
+            channels.remove(&board_id);
+        }
+    }
+
+    // 購読者が残っていなければチャンネルを破棄
+    pub fn release(&self, board_id: i64) {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        if channels
+            .get(&board_id)
+            .is_some_and(|sender| sender.receiver_count() == 0)
+        {
+            channels.remove(&board_id);
+        }
+    }
+}
@@ -1,56 +1,156 @@
 mod controllers {
     mod accounts;
+    mod actions;
     mod root;
     pub mod boards;
+    mod tickets;
 
     pub use accounts::accounts;
+    pub use actions::actions;
     pub use boards::boards;
     pub use root::app;
+    pub use tickets::tickets;
 }
 
+mod auth;
+
+mod crypto;
+
 mod database;
 
+mod https;
+
+mod error;
+
+mod events;
+
+mod notifier;
+
+mod oidc;
+
+mod rate_limit;
+
 mod entities {
     mod account;
+    mod action_item;
+    mod api_token;
     mod board;
+    mod board_column;
+    mod board_member;
+    mod category;
+    mod login_session;
+    mod password_reset;
+    mod refresh_token;
     mod ticket;
+    mod ticket_vote;
 
-    pub use account::Account;
-    pub use board::Board;
+    pub use account::{Account, DISPLAY_NAME_MAX_CHARS};
+    pub use action_item::{ActionItem, ActionStatus};
+    pub use api_token::{API_TOKEN_PREFIX, ApiScope, ApiToken};
+    pub use board::{Board, BoardPhase, BoardSettings};
+    pub use board_column::{BoardColumn, RetroTemplate};
+    pub use board_member::{BoardMember, BoardRole};
+    pub use category::Category;
+    pub use login_session::LoginSession;
+    pub use password_reset::PasswordResetToken;
+    pub use refresh_token::RefreshToken;
     pub use ticket::Ticket;
+    pub use ticket_vote::TicketVote;
 }
 
 mod repos_impl {
     mod accounts;
+    mod action_items;
+    mod api_tokens;
     mod boards;
+    mod identities;
+    mod login_attempts;
+    mod password_resets;
+    mod refresh_tokens;
+    mod sessions;
     mod tickets;
 
     pub use accounts::AccountsImpl;
+    pub use action_items::ActionItemsImpl;
+    pub use api_tokens::ApiTokensImpl;
     pub use boards::BoardsImpl;
+    pub use identities::IdentitiesImpl;
+    pub use login_attempts::{LoginAttemptsImpl, MemoryLoginAttempts};
+    pub use password_resets::PasswordResetsImpl;
+    pub use refresh_tokens::RefreshTokensImpl;
+    pub use sessions::SessionsImpl;
     pub use tickets::TicketsImpl;
 }
 
 pub mod repositories;
 
 
+mod policy;
+
 mod services {
     mod accounts;
+    mod actions;
+    mod api_tokens;
     mod boards;
+    mod jwt;
+    mod oidc;
     mod tickets;
 
-    pub use accounts::{create_account, create_session, SessionToken};
-    pub use boards::{get_all_boards, get_board_by_id, save_board, update_board, delete_board};
+    pub use accounts::{
+        Credentials, authenticate, change_display_name, change_password, create_account,
+        create_session, delete_account, delete_session, get_account, get_sessions,
+        request_password_reset, reset_password, revoke_session,
+    };
+    pub use actions::{
+        create_action_item, delete_action_item, get_board_action_items, get_my_action_items,
+        update_action_item,
+    };
+    pub use api_tokens::{create_api_token, get_api_tokens, revoke_api_token};
+    pub use boards::{
+        add_board_member, change_board_phase, delete_board, get_all_boards, get_board_by_id, get_board_columns,
+        get_board_members, remove_board_member, save_board, update_board, update_board_settings,
+    };
+    pub use jwt::{
+        TokenPair, get_token_families, issue_token_pair, refresh_token_pair, revoke_token_family,
+        revoke_token_family_by_id,
+    };
+    pub use oidc::{complete_oidc_login, find_or_provision_account, start_oidc_login};
     pub use tickets::{
-        get_all_tickets, save_ticket, update_ticket, delete_ticket,
+        VoteTally, delete_ticket, get_all_tickets, get_ticket, get_ticket_authors, get_vote_tally,
+        move_ticket, reorder_tickets, save_ticket, unvote_ticket, update_ticket, vote_ticket,
     };
 }
 
 mod request;
 
+mod state;
+
 pub use controllers::app;
 
 mod constants {
-    pub const AXUM_SESSION_COOKIE_NAME: &str = "rustwi_session";
     pub const AXUM_SESSION_USER_ID_KEY: &str = "uid";
+    pub const AXUM_SESSION_CREATED_AT_KEY: &str = "created_at";
+    pub const AXUM_SESSION_USER_AGENT_KEY: &str = "user_agent";
+    pub const AXUM_SESSION_OIDC_VERIFIER_KEY: &str = "oidc_verifier";
+    pub const AXUM_SESSION_OIDC_NONCE_KEY: &str = "oidc_nonce";
+    // ログインセッションの有効期間（7日）
+    pub const SESSION_TTL_SECS: u64 = 60 * 60 * 24 * 7;
+    // パスワードリセットトークンの有効期間（1時間）
+    pub const PASSWORD_RESET_TTL_SECS: i64 = 60 * 60;
+    // OIDC ログイン開始からコールバックまでの猶予（10分）
+    pub const OIDC_PENDING_TTL_SECS: u64 = 60 * 10;
     pub const ENV_KEY_DATABASE_URL: &str = "DATABASE_URL";
+    // memory（既定）または postgres
+    pub const ENV_KEY_LOGIN_RATE_LIMIT_STORE: &str = "LOGIN_RATE_LIMIT_STORE";
+    pub const ENV_KEY_LOGIN_MAX_FAILURES: &str = "LOGIN_MAX_FAILURES";
+    pub const ENV_KEY_LOGIN_LOCKOUT_SECS: &str = "LOGIN_LOCKOUT_SECS";
+    // session（既定）または jwt
+    pub const ENV_KEY_AUTH_MODE: &str = "AUTH_MODE";
+    pub const ENV_KEY_JWT_SECRET: &str = "JWT_SECRET";
+    pub const ENV_KEY_JWT_ACCESS_TTL_SECS: &str = "JWT_ACCESS_TTL_SECS";
+    // 未設定なら OIDC ログインは無効
+    pub const ENV_KEY_OIDC_ISSUER: &str = "OIDC_ISSUER";
+    pub const ENV_KEY_OIDC_CLIENT_ID: &str = "OIDC_CLIENT_ID";
+    pub const ENV_KEY_OIDC_CLIENT_SECRET: &str = "OIDC_CLIENT_SECRET";
+    pub const ENV_KEY_OIDC_REDIRECT_URI: &str = "OIDC_REDIRECT_URI";
 }
\ No newline at end of file
@@ -0,0 +1,23 @@
+use crate::entities::Account;
+use crate::error::AppError;
+
+// アカウント宛ての通知の送信手段（メールなどに差し替える）
+#[axum::async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send_password_reset(&self, account: &Account, token: &str) -> Result<(), AppError>;
+}
+
+// ローカル用：標準出力に書き出すだけ
+#[derive(Clone, Copy, Default)]
+pub struct LogNotifier;
+
+#[axum::async_trait]
+impl Notifier for LogNotifier {
+    async fn send_password_reset(&self, account: &Account, token: &str) -> Result<(), AppError> {
+        println!(
+            "[notifier] password reset token for {}: {}",
+            account.display_name, token
+        );
+        Ok(())
+    }
+}
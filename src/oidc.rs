@@ -0,0 +1,271 @@
+use axum::body::Bytes;
+use axum::http::{Method, Request, StatusCode, header};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use http_body_util::{BodyExt, Full};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::constants::{
+    ENV_KEY_OIDC_CLIENT_ID, ENV_KEY_OIDC_CLIENT_SECRET, ENV_KEY_OIDC_ISSUER,
+    ENV_KEY_OIDC_REDIRECT_URI,
+};
+use crate::error::AppError;
+use crate::https::{self, HttpClient};
+
+// 認可コードフロー（PKCE）で使う IdP の設定
+// エンドポイントは issuer の discovery から取得する
+#[derive(Clone)]
+pub struct OidcClient {
+    http: HttpClient,
+    pub issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+    jwks_uri: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub id_token: String,
+}
+
+// 検証済みの ID トークンのクレーム（iss・aud・exp はデコード時に検証する）
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserInfo {
+    pub sub: String,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+}
+
+impl OidcClient {
+    // OIDC_ISSUER が未設定なら無効（None）
+    pub async fn from_env() -> Option<OidcClient> {
+        let issuer = std::env::var(ENV_KEY_OIDC_ISSUER).ok()?;
+        let client_id = std::env::var(ENV_KEY_OIDC_CLIENT_ID)
+            .expect("OIDC_CLIENT_ID must be set when OIDC_ISSUER is set");
+        let redirect_uri = std::env::var(ENV_KEY_OIDC_REDIRECT_URI)
+            .expect("OIDC_REDIRECT_URI must be set when OIDC_ISSUER is set");
+        let client_secret = std::env::var(ENV_KEY_OIDC_CLIENT_SECRET).ok();
+
+        // IdP に繋がらなくてもパスワードログインは使えるよう起動は続ける
+        match OidcClient::discover(&issuer, client_id, client_secret, redirect_uri).await {
+            Ok(client) => Some(client),
+            Err(e) => {
+                tracing::error!("OIDC discovery failed for {}: {}", issuer, e);
+                None
+            }
+        }
+    }
+
+    pub async fn discover(
+        issuer: &str,
+        client_id: String,
+        client_secret: Option<String>,
+        redirect_uri: String,
+    ) -> Result<OidcClient, AppError> {
+        let http = https::client();
+        let issuer = issuer.trim_end_matches('/');
+        let url = format!("{}/.well-known/openid-configuration", issuer);
+        let discovery: Discovery = get_json(&http, &url, None).await?;
+
+        if discovery.issuer.trim_end_matches('/') != issuer {
+            return Err(AppError::Internal(format!(
+                "OIDC issuer mismatch: expected {}, got {}",
+                issuer, discovery.issuer
+            )));
+        }
+
+        Ok(OidcClient {
+            http,
+            issuer: discovery.issuer,
+            authorization_endpoint: discovery.authorization_endpoint,
+            token_endpoint: discovery.token_endpoint,
+            userinfo_endpoint: discovery.userinfo_endpoint,
+            jwks_uri: discovery.jwks_uri,
+            client_id,
+            client_secret,
+            redirect_uri,
+        })
+    }
+
+    // state はコールバックで照合する値、code_verifier はトークン交換まで保持する値
+    // nonce は ID トークンに含まれて返る値
+    pub fn authorization_url(
+        &self,
+        state: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<String, AppError> {
+        let mut url = Url::parse(&self.authorization_endpoint)
+            .map_err(|e| AppError::Internal(format!("Invalid authorization endpoint: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("scope", "openid profile")
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &pkce_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    // 認可コードをアクセストークンと ID トークンに交換する
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<TokenResponse, AppError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let body =
+            serde_urlencoded::to_string(&form).map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(&self.token_endpoint)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::ACCEPT, "application/json")
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let (status, body) = send(&self.http, request).await?;
+        if !status.is_success() {
+            return Err(AppError::Unauthorized(
+                "Authorization code was rejected by the identity provider".to_string(),
+            ));
+        }
+        parse_json(&body)
+    }
+
+    // IdP の公開鍵（jwks_uri）で署名を検証し、iss・aud・exp・nonce を確認する
+    pub async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let invalid = || AppError::Unauthorized("ID token is invalid".to_string());
+
+        let header = jsonwebtoken::decode_header(id_token).map_err(|_| invalid())?;
+        // 共有鍵による署名（HS*）は受け付けない
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid());
+        }
+
+        // 鍵の更新に追従するため毎回取得する
+        let jwks: JwkSet = get_json(&self.http, &self.jwks_uri, None).await?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(invalid)?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid())?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|_| invalid())?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid());
+        }
+        Ok(claims)
+    }
+
+    pub async fn user_info(&self, access_token: &str) -> Result<UserInfo, AppError> {
+        get_json(&self.http, &self.userinfo_endpoint, Some(access_token)).await
+    }
+}
+
+// S256: BASE64URL(SHA256(code_verifier))
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+async fn get_json<T: DeserializeOwned>(
+    http: &HttpClient,
+    url: &str,
+    bearer: Option<&str>,
+) -> Result<T, AppError> {
+    let mut request = Request::builder()
+        .method(Method::GET)
+        .uri(url)
+        .header(header::ACCEPT, "application/json");
+    if let Some(token) = bearer {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request
+        .body(Full::new(Bytes::new()))
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let (status, body) = send(http, request).await?;
+    if !status.is_success() {
+        return Err(AppError::Internal(format!(
+            "Identity provider returned {} for {}",
+            status, url
+        )));
+    }
+    parse_json(&body)
+}
+
+async fn send(
+    http: &HttpClient,
+    request: Request<Full<Bytes>>,
+) -> Result<(StatusCode, Bytes), AppError> {
+    let response = http
+        .request(request)
+        .await
+        .map_err(|e| AppError::Internal(format!("Identity provider request failed: {}", e)))?;
+    let status = response.status();
+    let body = response
+        .into_body()
+        .collect()
+        .await
+        .map_err(|e| AppError::Internal(format!("Identity provider request failed: {}", e)))?
+        .to_bytes();
+    Ok((status, body))
+}
+
+fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, AppError> {
+    serde_json::from_slice(body)
+        .map_err(|e| AppError::Internal(format!("Invalid identity provider response: {}", e)))
+}
@@ -0,0 +1,331 @@
+use crate::entities::{ActionItem, ApiScope, BoardRole, Ticket};
+use crate::request::UserContext;
+
+// ボード・チケットに対する権限判定
+// role はユーザーのボード上のロール（メンバーでなければ None）
+pub trait Policy {
+    fn can_list_boards(&self, user: &UserContext) -> bool;
+    fn can_create_board(&self, user: &UserContext) -> bool;
+    fn can_read_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_edit_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_delete_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_manage_members(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_leave_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_change_settings(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_create_ticket(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_vote(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_edit_ticket(&self, user: &UserContext, role: Option<BoardRole>, ticket: &Ticket)
+    -> bool;
+    fn can_delete_ticket(
+        &self,
+        user: &UserContext,
+        role: Option<BoardRole>,
+        ticket: &Ticket,
+    ) -> bool;
+    fn can_manage_actions(&self, user: &UserContext, role: Option<BoardRole>) -> bool;
+    fn can_update_action_status(
+        &self,
+        user: &UserContext,
+        role: Option<BoardRole>,
+        action: &ActionItem,
+    ) -> bool;
+}
+
+// ロールに基づく標準ポリシー
+// owner: すべて可能 / editor: ボード編集・チケット作成・投票・アクションアイテムの管理、自分のチケットのみ編集削除
+// viewer: 閲覧と、自分が担当のアクションアイテムの状態変更のみ
+// 個人用トークンでは閲覧に boards:read、それ以外に boards:write が必要
+#[derive(Clone, Copy, Default)]
+pub struct RolePolicy;
+
+impl Policy for RolePolicy {
+    fn can_list_boards(&self, user: &UserContext) -> bool {
+        user.has_scope(ApiScope::BoardsRead)
+    }
+
+    fn can_create_board(&self, user: &UserContext) -> bool {
+        user.has_scope(ApiScope::BoardsWrite)
+    }
+
+    fn can_read_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsRead) && role.is_some()
+    }
+
+    fn can_edit_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite)
+            && matches!(role, Some(BoardRole::Owner | BoardRole::Editor))
+    }
+
+    fn can_delete_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite) && role == Some(BoardRole::Owner)
+    }
+
+    fn can_manage_members(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite) && role == Some(BoardRole::Owner)
+    }
+
+    // 本人による退出（メンバーであれば可）
+    fn can_leave_board(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite) && role.is_some()
+    }
+
+    fn can_change_settings(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite) && role == Some(BoardRole::Owner)
+    }
+
+    fn can_create_ticket(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite)
+            && matches!(role, Some(BoardRole::Owner | BoardRole::Editor))
+    }
+
+    fn can_vote(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite)
+            && matches!(role, Some(BoardRole::Owner | BoardRole::Editor))
+    }
+
+    fn can_edit_ticket(
+        &self,
+        user: &UserContext,
+        role: Option<BoardRole>,
+        ticket: &Ticket,
+    ) -> bool {
+        if !user.has_scope(ApiScope::BoardsWrite) {
+            return false;
+        }
+        match role {
+            Some(BoardRole::Owner) => true,
+            Some(BoardRole::Editor) => ticket.author_id == user.user_id,
+            _ => false,
+        }
+    }
+
+    fn can_delete_ticket(
+        &self,
+        user: &UserContext,
+        role: Option<BoardRole>,
+        ticket: &Ticket,
+    ) -> bool {
+        self.can_edit_ticket(user, role, ticket)
+    }
+
+    fn can_manage_actions(&self, user: &UserContext, role: Option<BoardRole>) -> bool {
+        user.has_scope(ApiScope::BoardsWrite)
+            && matches!(role, Some(BoardRole::Owner | BoardRole::Editor))
+    }
+
+    // 担当者は状態のみ変更できる（内容・期限・担当の変更は can_manage_actions）
+    fn can_update_action_status(
+        &self,
+        user: &UserContext,
+        role: Option<BoardRole>,
+        action: &ActionItem,
+    ) -> bool {
+        self.can_manage_actions(user, role)
+            || (user.has_scope(ApiScope::BoardsWrite)
+                && role.is_some()
+                && action.assignee_id == user.user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Category;
+
+    const USER_ID: i64 = 1;
+    const OTHER_ID: i64 = 2;
+
+    const ROLES: [Option<BoardRole>; 4] = [
+        Some(BoardRole::Owner),
+        Some(BoardRole::Editor),
+        Some(BoardRole::Viewer),
+        None,
+    ];
+
+    fn user() -> UserContext {
+        UserContext {
+            user_id: USER_ID,
+            session_id: String::new(),
+            scopes: None,
+        }
+    }
+
+    fn token_user(scopes: &[ApiScope]) -> UserContext {
+        UserContext {
+            scopes: Some(scopes.to_vec()),
+            ..user()
+        }
+    }
+
+    fn ticket_by(author_id: i64) -> Ticket {
+        Ticket::create(10, author_id, Category::Keep, "content".to_string())
+    }
+
+    fn action_for(assignee_id: i64) -> ActionItem {
+        let mut ticket = Ticket::create(10, OTHER_ID, Category::Try, "try".to_string());
+        ticket.id = Some(20);
+        let due = chrono::NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        ActionItem::create(&ticket, OTHER_ID, None, assignee_id, due).unwrap()
+    }
+
+    // ROLES と同じ順に期待値を並べる
+    fn assert_for_roles(expected: [bool; 4], check: impl Fn(Option<BoardRole>) -> bool) {
+        for (role, expected) in ROLES.into_iter().zip(expected) {
+            assert_eq!(check(role), expected, "role: {:?}", role);
+        }
+    }
+
+    #[test]
+    fn any_user_can_create_board() {
+        assert!(RolePolicy.can_list_boards(&user()));
+        assert!(RolePolicy.can_create_board(&user()));
+    }
+
+    #[test]
+    fn read_board() {
+        assert_for_roles([true, true, true, false], |role| {
+            RolePolicy.can_read_board(&user(), role)
+        });
+    }
+
+    #[test]
+    fn edit_board() {
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_edit_board(&user(), role)
+        });
+    }
+
+    #[test]
+    fn delete_board() {
+        assert_for_roles([true, false, false, false], |role| {
+            RolePolicy.can_delete_board(&user(), role)
+        });
+    }
+
+    #[test]
+    fn manage_members() {
+        assert_for_roles([true, false, false, false], |role| {
+            RolePolicy.can_manage_members(&user(), role)
+        });
+    }
+
+    #[test]
+    fn leave_board() {
+        assert_for_roles([true, true, true, false], |role| {
+            RolePolicy.can_leave_board(&user(), role)
+        });
+    }
+
+    #[test]
+    fn change_settings() {
+        assert_for_roles([true, false, false, false], |role| {
+            RolePolicy.can_change_settings(&user(), role)
+        });
+    }
+
+    #[test]
+    fn create_ticket() {
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_create_ticket(&user(), role)
+        });
+    }
+
+    #[test]
+    fn vote() {
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_vote(&user(), role)
+        });
+    }
+
+    #[test]
+    fn edit_own_ticket() {
+        let ticket = ticket_by(USER_ID);
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_edit_ticket(&user(), role, &ticket)
+        });
+    }
+
+    #[test]
+    fn edit_others_ticket() {
+        let ticket = ticket_by(OTHER_ID);
+        assert_for_roles([true, false, false, false], |role| {
+            RolePolicy.can_edit_ticket(&user(), role, &ticket)
+        });
+    }
+
+    #[test]
+    fn delete_own_ticket() {
+        let ticket = ticket_by(USER_ID);
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_delete_ticket(&user(), role, &ticket)
+        });
+    }
+
+    #[test]
+    fn delete_others_ticket() {
+        let ticket = ticket_by(OTHER_ID);
+        assert_for_roles([true, false, false, false], |role| {
+            RolePolicy.can_delete_ticket(&user(), role, &ticket)
+        });
+    }
+
+    #[test]
+    fn manage_actions() {
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_manage_actions(&user(), role)
+        });
+    }
+
+    #[test]
+    fn update_others_action_status() {
+        let action = action_for(OTHER_ID);
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_update_action_status(&user(), role, &action)
+        });
+    }
+
+    #[test]
+    fn update_assigned_action_status() {
+        let action = action_for(USER_ID);
+        assert_for_roles([true, true, true, false], |role| {
+            RolePolicy.can_update_action_status(&user(), role, &action)
+        });
+    }
+
+    #[test]
+    fn read_scope_token_can_only_read() {
+        let reader = token_user(&[ApiScope::BoardsRead]);
+        let ticket = ticket_by(USER_ID);
+        let action = action_for(USER_ID);
+        assert!(RolePolicy.can_list_boards(&reader));
+        assert!(!RolePolicy.can_create_board(&reader));
+        assert_for_roles([true, true, true, false], |role| {
+            RolePolicy.can_read_board(&reader, role)
+        });
+        assert_for_roles([false, false, false, false], |role| {
+            RolePolicy.can_edit_board(&reader, role)
+                || RolePolicy.can_delete_board(&reader, role)
+                || RolePolicy.can_manage_members(&reader, role)
+                || RolePolicy.can_leave_board(&reader, role)
+                || RolePolicy.can_change_settings(&reader, role)
+                || RolePolicy.can_create_ticket(&reader, role)
+                || RolePolicy.can_vote(&reader, role)
+                || RolePolicy.can_edit_ticket(&reader, role, &ticket)
+                || RolePolicy.can_delete_ticket(&reader, role, &ticket)
+                || RolePolicy.can_manage_actions(&reader, role)
+                || RolePolicy.can_update_action_status(&reader, role, &action)
+        });
+    }
+
+    #[test]
+    fn write_scope_token_follows_roles() {
+        let writer = token_user(&[ApiScope::BoardsWrite]);
+        assert!(RolePolicy.can_create_board(&writer));
+        assert_for_roles([true, true, true, false], |role| {
+            RolePolicy.can_read_board(&writer, role)
+        });
+        assert_for_roles([true, true, false, false], |role| {
+            RolePolicy.can_create_ticket(&writer, role)
+        });
+    }
+}
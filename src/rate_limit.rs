@@ -0,0 +1,204 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::constants::{
+    ENV_KEY_LOGIN_LOCKOUT_SECS, ENV_KEY_LOGIN_MAX_FAILURES, ENV_KEY_LOGIN_RATE_LIMIT_STORE,
+};
+use crate::database::Repositories;
+use crate::error::AppError;
+use crate::repos_impl::MemoryLoginAttempts;
+use crate::repositories::login_attempts::LoginAttempts;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LimitConfig {
+    // この回数失敗したらロックする
+    pub max_failures: i32,
+    // 最初のロック時間（以降は失敗のたびに倍）
+    pub base_lockout: Duration,
+    pub max_lockout: Duration,
+    // 最後の失敗からこの時間が経てば失敗回数を数え直す
+    pub window: Duration,
+}
+
+impl Default for LimitConfig {
+    fn default() -> Self {
+        LimitConfig {
+            max_failures: 5,
+            base_lockout: Duration::seconds(30),
+            max_lockout: Duration::minutes(15),
+            window: Duration::minutes(15),
+        }
+    }
+}
+
+// 環境変数で指定できる値の範囲
+const MAX_FAILURES_LIMIT: i32 = 100;
+const LOCKOUT_SECS_LIMIT: i64 = 24 * 60 * 60;
+
+impl LimitConfig {
+    fn lockout_for(&self, failures: i32) -> Duration {
+        let doublings = failures.saturating_sub(self.max_failures).clamp(0, 16) as u32;
+        self.base_lockout
+            .checked_mul(2_i32.pow(doublings))
+            .unwrap_or(self.max_lockout)
+            .min(self.max_lockout)
+    }
+}
+
+// 表示名ごと・IPアドレスごとにログイン失敗を数え、超過したら一定時間ロックする
+#[derive(Clone)]
+pub struct LoginLimiter {
+    store: Arc<dyn LoginAttempts>,
+    config: LimitConfig,
+}
+
+impl LoginLimiter {
+    pub fn new(store: Arc<dyn LoginAttempts>, config: LimitConfig) -> LoginLimiter {
+        LoginLimiter { store, config }
+    }
+
+    // LOGIN_RATE_LIMIT_STORE=postgres で複数インスタンス間で共有する（既定はメモリ）
+    pub fn from_env(repos: &Repositories) -> LoginLimiter {
+        let mut config = LimitConfig::default();
+        // 極端な値は範囲内に丸める
+        if let Some(max_failures) = env_parse::<i32>(ENV_KEY_LOGIN_MAX_FAILURES) {
+            config.max_failures = max_failures.clamp(1, MAX_FAILURES_LIMIT);
+        }
+        if let Some(secs) = env_parse::<i64>(ENV_KEY_LOGIN_LOCKOUT_SECS) {
+            config.base_lockout = Duration::seconds(secs.clamp(1, LOCKOUT_SECS_LIMIT));
+            config.max_lockout = config.max_lockout.max(config.base_lockout);
+        }
+
+        let store: Arc<dyn LoginAttempts> =
+            match std::env::var(ENV_KEY_LOGIN_RATE_LIMIT_STORE).as_deref() {
+                Ok("postgres") => Arc::new(repos.login_attempts.clone()),
+                _ => Arc::new(MemoryLoginAttempts::default()),
+            };
+        LoginLimiter::new(store, config)
+    }
+
+    // ロック中のキーがあれば 429
+    pub async fn check(&self, display_name: &str, ip: Option<IpAddr>) -> Result<(), AppError> {
+        let now = Utc::now();
+        let mut locked_until = None;
+        for key in keys(display_name, ip) {
+            if let Some(until) = self.store.get(&key).await?.and_then(|a| a.locked_until)
+                && until > now
+            {
+                locked_until = locked_until.max(Some(until));
+            }
+        }
+
+        match locked_until {
+            Some(until) => Err(AppError::TooManyRequests {
+                message: "Too many failed login attempts".to_string(),
+                retry_after: (until - now).to_std().unwrap_or_default(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn record_failure(
+        &self,
+        display_name: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), AppError> {
+        for key in keys(display_name, ip) {
+            let failures = self.store.increment(&key, self.config.window).await?;
+            if failures >= self.config.max_failures {
+                let until = Utc::now() + self.config.lockout_for(failures);
+                self.store.lock(&key, until).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // 成功時は表示名側のみリセット（同じIPからの総当たりは引き続き数える）
+    pub async fn record_success(&self, display_name: &str) -> Result<(), AppError> {
+        self.store.clear(&name_key(display_name)).await
+    }
+}
+
+fn keys(display_name: &str, ip: Option<IpAddr>) -> Vec<String> {
+    let mut keys = vec![name_key(display_name)];
+    if let Some(ip) = ip {
+        keys.push(format!("ip:{}", ip));
+    }
+    keys
+}
+
+fn name_key(display_name: &str) -> String {
+    format!("name:{}", display_name)
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "alice";
+
+    fn limiter(config: LimitConfig) -> LoginLimiter {
+        LoginLimiter::new(Arc::new(MemoryLoginAttempts::default()), config)
+    }
+
+    fn is_locked(result: Result<(), AppError>) -> bool {
+        matches!(result, Err(AppError::TooManyRequests { .. }))
+    }
+
+    #[tokio::test]
+    async fn locks_after_max_failures() {
+        let limiter = limiter(LimitConfig {
+            max_failures: 3,
+            ..LimitConfig::default()
+        });
+        for _ in 0..2 {
+            limiter.record_failure(NAME, None).await.unwrap();
+        }
+        assert!(!is_locked(limiter.check(NAME, None).await));
+
+        limiter.record_failure(NAME, None).await.unwrap();
+        assert!(is_locked(limiter.check(NAME, None).await));
+        assert!(!is_locked(limiter.check("bob", None).await));
+    }
+
+    #[test]
+    fn lockout_doubles_up_to_cap() {
+        let config = LimitConfig::default();
+        assert_eq!(config.lockout_for(5), Duration::seconds(30));
+        assert_eq!(config.lockout_for(6), Duration::seconds(60));
+        assert_eq!(config.lockout_for(7), Duration::seconds(120));
+        assert_eq!(config.lockout_for(100), config.max_lockout);
+        assert_eq!(config.lockout_for(i32::MAX), config.max_lockout);
+    }
+
+    #[test]
+    fn lockout_does_not_overflow() {
+        let config = LimitConfig {
+            max_failures: i32::MIN,
+            base_lockout: Duration::MAX,
+            max_lockout: Duration::MAX,
+            ..LimitConfig::default()
+        };
+        assert_eq!(config.lockout_for(i32::MAX), Duration::MAX);
+    }
+
+    #[tokio::test]
+    async fn failures_reset_after_window() {
+        let limiter = limiter(LimitConfig {
+            max_failures: 2,
+            window: Duration::milliseconds(50),
+            ..LimitConfig::default()
+        });
+        limiter.record_failure(NAME, None).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        limiter.record_failure(NAME, None).await.unwrap();
+        assert!(!is_locked(limiter.check(NAME, None).await));
+    }
+}
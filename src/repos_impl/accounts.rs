@@ -73,26 +73,42 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use bb8::Pool;
-use bb8_postgres::PostgresConnectionManager;
-use tokio_postgres::{NoTls, Row};
+use tokio_postgres::Row;
+use tokio_postgres::error::SqlState;
 
+use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
 use crate::entities::Account;
+use crate::error::AppError;
 use crate::repositories::accounts::Accounts;
 
 #[derive(Clone)]
 pub struct AccountsImpl {
-    pub pool: Arc<Pool<PostgresConnectionManager<NoTls>>>,
+    pub pool: Arc<DbPool>,
+    pub tx: Option<UnitOfWork>,
+}
+
+impl AccountsImpl {
+    // 共有トランザクションに参加したリポジトリを返す
+    pub fn join(&self, tx: &UnitOfWork) -> AccountsImpl {
+        AccountsImpl {
+            pool: self.pool.clone(),
+            tx: Some(tx.clone()),
+        }
+    }
+
+    async fn client(&self) -> Result<DbClient<'_>, AppError> {
+        acquire(&self.pool, self.tx.as_ref()).await
+    }
 }
 
 #[axum::async_trait]
 impl Accounts for AccountsImpl {
-    async fn find(&self, ids: HashSet<i64>) -> HashMap<i64, Account> {
+    async fn find(&self, ids: HashSet<i64>) -> Result<HashMap<i64, Account>, AppError> {
         if ids.is_empty() {
-            return HashMap::new();
+            return Ok(HashMap::new());
         }
 
-        let conn = self.pool.get().await.unwrap();
+        let conn = self.client().await?;
 
         let placeholders: Vec<String> = (1..=ids.len()).map(|i| format!("${}", i)).collect();
         let query = format!(
@@ -100,51 +116,129 @@ impl Accounts for AccountsImpl {
             placeholders.join(",")
         );
 
-        let params: Vec<i64> = ids.iter().map(|id| *id as i64).collect();
+        let params: Vec<i64> = ids.into_iter().collect();
         let params_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
             params.iter().map(|id| id as _).collect();
 
-        let rows = conn.query(&query, &params_refs[..]).await.unwrap();
+        let rows = conn.query(&query, &params_refs[..]).await?;
 
-        rows.into_iter()
-            .map(|row| {
-                let account = row_to_account(&row);
-                (account.id().unwrap(), account)
-            })
-            .collect()
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("id"), row_to_account(&row)))
+            .collect())
     }
 
-    async fn find_by(&self, display_name: &str) -> Option<Account> {
-        let conn = self.pool.get().await.ok()?;
-        let row = conn
+    async fn find_by(&self, display_name: &str) -> Result<Option<Account>, AppError> {
+        let conn = self.client().await?;
+        let row_opt = conn
             .query_opt(
-                "SELECT * FROM accounts WHERE display_name = $1",
+                "SELECT * FROM accounts WHERE display_name = $1 AND deleted_at IS NULL",
                 &[&display_name],
             )
+            .await?;
+
+        Ok(row_opt.map(|row| row_to_account(&row)))
+    }
+
+    async fn store(&self, entity: &Account) -> Result<i64, AppError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO accounts (password, display_name) VALUES ($1, $2) RETURNING id",
+                &[&entity.hashed_password, &entity.display_name],
+            )
             .await
-            .ok()??;
+            .map_err(|e| match e.code() {
+                Some(&SqlState::UNIQUE_VIOLATION) => {
+                    AppError::conflict("Display name is already taken")
+                }
+                _ => e.into(),
+            })?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn update_password(&self, entity: &Account) -> Result<(), AppError> {
+        let id = entity
+            .id()
+            .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))?;
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE accounts SET password = $1 WHERE id = $2",
+                &[&entity.hashed_password, &id],
+            )
+            .await?;
 
-        Some(row_to_account(&row))
+        Ok(())
     }
 
-    async fn store(&self, entity: &Account) {
-        let conn = self.pool.get().await.ok();
-        if let Some(client) = conn {
-            let _ = client
+    async fn update_display_name(&self, entity: &Account) -> Result<(), AppError> {
+        let id = entity
+            .id()
+            .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))?;
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE accounts SET display_name = $1 WHERE id = $2",
+                &[&entity.display_name, &id],
+            )
+            .await
+            .map_err(|e| match e.code() {
+                Some(&SqlState::UNIQUE_VIOLATION) => {
+                    AppError::conflict("Display name is already taken")
+                }
+                _ => e.into(),
+            })?;
+
+        Ok(())
+    }
+
+    async fn anonymize(&self, entity: &Account) -> Result<(), AppError> {
+        let id = entity
+            .id()
+            .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))?;
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE accounts SET display_name = $1, password = $2, is_admin = $3, deleted_at = $4
+                 WHERE id = $5",
+                &[
+                    &entity.display_name,
+                    &entity.hashed_password,
+                    &entity.is_admin,
+                    &entity.deleted_at,
+                    &id,
+                ],
+            )
+            .await?;
+
+        // セッションは Sessions で削除する
+        for table in [
+            "api_token",
+            "account_identity",
+            "refresh_token",
+            "password_reset_token",
+        ] {
+            client
                 .execute(
-                    "INSERT INTO accounts (password, display_name) VALUES ($1, $2)",
-                    &[&entity.hashed_password, &entity.display_name],
+                    &format!("DELETE FROM {} WHERE account_id = $1", table),
+                    &[&id],
                 )
-                .await.expect("Failed to execute insert");
+                .await?;
         }
+
+        Ok(())
     }
 }
 
 fn row_to_account(row: &Row) -> Account {
-    let id: i64 = row.get("id");
-    Account::new(
-        Some(id as i64),
+    let mut account = Account::new(
+        Some(row.get("id")),
         row.get("password"),
         row.get("display_name"),
-    )
+    );
+    account.is_admin = row.get("is_admin");
+    account.deleted_at = row.get("deleted_at");
+    account
 }
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use tokio_postgres::Row;
+
+use crate::database::DbPool;
+use crate::entities::ActionItem;
+use crate::error::AppError;
+use crate::repositories::action_items::ActionItems;
+
+#[derive(Clone)]
+pub struct ActionItemsImpl {
+    pub pool: Arc<DbPool>,
+}
+
+#[axum::async_trait]
+impl ActionItems for ActionItemsImpl {
+    async fn find(&self, id: i64) -> Result<Option<ActionItem>, AppError> {
+        let client = self.pool.get().await?;
+
+        let row_opt = client
+            .query_opt("SELECT * FROM action_item WHERE id = $1", &[&id])
+            .await?;
+
+        row_opt.as_ref().map(row_to_action_item).transpose()
+    }
+
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<ActionItem>, AppError> {
+        let client = self.pool.get().await?;
+
+        let rows = client
+            .query(
+                "SELECT * FROM action_item WHERE board_id = $1 ORDER BY due_date, id",
+                &[&board_id],
+            )
+            .await?;
+
+        rows.iter().map(row_to_action_item).collect()
+    }
+
+    async fn find_by_assignee(&self, account_id: i64) -> Result<Vec<ActionItem>, AppError> {
+        let client = self.pool.get().await?;
+
+        // 担当者がまだ閲覧できる（作成者またはメンバーである）ボードのもののみ
+        let rows = client
+            .query(
+                "SELECT a.* FROM action_item a JOIN board b ON b.id = a.board_id
+                 WHERE a.assignee_id = $1 AND b.deleted = FALSE
+                   AND (b.created_by = $1
+                        OR EXISTS (SELECT 1 FROM board_member m
+                                   WHERE m.board_id = b.id AND m.account_id = $1))
+                 ORDER BY a.due_date, a.id",
+                &[&account_id],
+            )
+            .await?;
+
+        rows.iter().map(row_to_action_item).collect()
+    }
+
+    async fn store(&self, entity: &ActionItem) -> Result<i64, AppError> {
+        let client = self.pool.get().await?;
+
+        let row = client
+            .query_one(
+                "INSERT INTO action_item
+                     (board_id, ticket_id, title, assignee_id, due_date, status, created_by)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 RETURNING id",
+                &[
+                    &entity.board_id,
+                    &entity.ticket_id,
+                    &entity.title,
+                    &entity.assignee_id,
+                    &entity.due_date,
+                    &entity.status.as_str(),
+                    &entity.created_by,
+                ],
+            )
+            .await?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn update(&self, entity: &ActionItem) -> Result<(), AppError> {
+        let id = entity
+            .id
+            .ok_or_else(|| AppError::validation("Action item ID is not set"))?;
+        let client = self.pool.get().await?;
+
+        client
+            .execute(
+                "UPDATE action_item
+                 SET title = $1, assignee_id = $2, due_date = $3, status = $4, updated_at = NOW()
+                 WHERE id = $5",
+                &[
+                    &entity.title,
+                    &entity.assignee_id,
+                    &entity.due_date,
+                    &entity.status.as_str(),
+                    &id,
+                ],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+
+        client
+            .execute("DELETE FROM action_item WHERE id = $1", &[&id])
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_action_item(row: &Row) -> Result<ActionItem, AppError> {
+    Ok(ActionItem {
+        id: Some(row.get("id")),
+        board_id: row.get("board_id"),
+        ticket_id: row.get("ticket_id"),
+        title: row.get("title"),
+        assignee_id: row.get("assignee_id"),
+        due_date: row.get("due_date"),
+        status: row
+            .get::<_, String>("status")
+            .parse()
+            .map_err(AppError::Database)?,
+        created_by: row.get("created_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
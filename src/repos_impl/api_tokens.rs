@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use tokio_postgres::Row;
+
+use crate::database::DbPool;
+use crate::entities::{ApiScope, ApiToken};
+use crate::error::AppError;
+use crate::repositories::api_tokens::ApiTokens;
+
+#[derive(Clone)]
+pub struct ApiTokensImpl {
+    pub pool: Arc<DbPool>,
+}
+
+#[axum::async_trait]
+impl ApiTokens for ApiTokensImpl {
+    async fn store(&self, entity: &ApiToken) -> Result<i64, AppError> {
+        let client = self.pool.get().await?;
+
+        let scopes: Vec<&str> = entity.scopes.iter().map(ApiScope::as_str).collect();
+        let row = client
+            .query_one(
+                "INSERT INTO api_token (account_id, name, token_hash, scopes, expires_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 RETURNING id",
+                &[
+                    &entity.account_id,
+                    &entity.name,
+                    &entity.token_hash,
+                    &scopes,
+                    &entity.expires_at,
+                ],
+            )
+            .await?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn find_by_account(&self, account_id: i64) -> Result<Vec<ApiToken>, AppError> {
+        let client = self.pool.get().await?;
+
+        let rows = client
+            .query(
+                "SELECT * FROM api_token WHERE account_id = $1 ORDER BY created_at DESC",
+                &[&account_id],
+            )
+            .await?;
+
+        rows.iter().map(row_to_api_token).collect()
+    }
+
+    async fn use_token(&self, token_hash: &str) -> Result<Option<ApiToken>, AppError> {
+        let client = self.pool.get().await?;
+
+        let row_opt = client
+            .query_opt(
+                "UPDATE api_token SET last_used_at = NOW()
+                 WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > NOW())
+                 RETURNING *",
+                &[&token_hash],
+            )
+            .await?;
+
+        row_opt.as_ref().map(row_to_api_token).transpose()
+    }
+
+    async fn delete(&self, account_id: i64, id: i64) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+
+        let deleted = client
+            .execute(
+                "DELETE FROM api_token WHERE id = $1 AND account_id = $2",
+                &[&id, &account_id],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+}
+
+fn row_to_api_token(row: &Row) -> Result<ApiToken, AppError> {
+    let scopes = row
+        .get::<_, Vec<String>>("scopes")
+        .iter()
+        .map(|s| s.parse())
+        .collect::<Result<Vec<ApiScope>, String>>()
+        .map_err(AppError::Database)?;
+
+    Ok(ApiToken {
+        id: Some(row.get("id")),
+        account_id: row.get("account_id"),
+        name: row.get("name"),
+        token_hash: row.get("token_hash"),
+        scopes,
+        expires_at: row.get("expires_at"),
+        created_at: row.get("created_at"),
+        last_used_at: row.get("last_used_at"),
+    })
+}
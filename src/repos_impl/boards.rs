@@ -1,12 +1,12 @@
 // use sqlx::{Row, mysql::MySqlRow};
 
-// use crate::database::DbPool;
+// use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
 // use crate::entities::Board;
 // use crate::repositories::boards::Boards;
 
 // #[derive(Clone)]
 // pub struct BoardsImpl {
-//     pub pool: DbPool,
+//     pub pool: Arc<DbPool>,
 // }
 
 // #[axum::async_trait]
@@ -102,135 +102,329 @@
 //     }
 // }
 
-use bb8::Pool;
-use bb8_postgres::PostgresConnectionManager;
 use std::sync::Arc;
-use tokio_postgres::{NoTls, Row};
+use tokio_postgres::Row;
+use tokio_postgres::types::ToSql;
 
-use crate::database::DbPool;
-use crate::entities::Board;
+use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
+use crate::entities::{Board, BoardColumn, BoardMember, BoardRole};
+use crate::error::AppError;
 use crate::repositories::boards::Boards;
-use anyhow::Result;
-use tokio_postgres::types::ToSql;
 
 #[derive(Clone)]
 pub struct BoardsImpl {
-     pub pool: Arc<Pool<PostgresConnectionManager<NoTls>>>,
+    pub pool: Arc<DbPool>,
+    pub tx: Option<UnitOfWork>,
+}
+
+impl BoardsImpl {
+    // 共有トランザクションに参加したリポジトリを返す
+    pub fn join(&self, tx: &UnitOfWork) -> BoardsImpl {
+        BoardsImpl {
+            pool: self.pool.clone(),
+            tx: Some(tx.clone()),
+        }
+    }
+
+    async fn client(&self) -> Result<DbClient<'_>, AppError> {
+        acquire(&self.pool, self.tx.as_ref()).await
+    }
 }
 
 #[axum::async_trait]
 impl Boards for BoardsImpl {
-    async fn find(&self, id: i64) -> Result<Option<Board>, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn find(&self, id: i64) -> Result<Option<Board>, AppError> {
+        let client = self.client().await?;
 
         let row_opt = client
             .query_opt(
                 "SELECT * FROM board WHERE id = $1",
-                &[&(id as i64) as &(dyn ToSql + Sync)],
+                &[&id as &(dyn ToSql + Sync)],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
-        Ok(row_opt.map(|row| row_to_board(&row)))
+        row_opt.map(|row| row_to_board(&row)).transpose()
     }
 
-    async fn find_by_title(&self, title: &str) -> Result<Vec<Board>, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn find_by_title(&self, title: &str) -> Result<Vec<Board>, AppError> {
+        let client = self.client().await?;
 
         let rows = client
             .query("SELECT * FROM board WHERE title = $1", &[&title])
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
-        Ok(rows.into_iter().map(|r| row_to_board(&r)).collect())
+        rows.iter().map(row_to_board).collect()
     }
 
-    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Board>, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
-        let user_id_i64 = user_id as i64;
+    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Board>, AppError> {
+        let client = self.client().await?;
 
+        // 作成者または共有メンバーになっているボード
         let rows = client
             .query(
-                "SELECT * FROM board WHERE created_by = $1 AND deleted = false",
-                &[&user_id_i64],
+                "SELECT b.* FROM board b
+                 WHERE b.deleted = false
+                   AND (b.created_by = $1
+                        OR EXISTS (SELECT 1 FROM board_member m
+                                   WHERE m.board_id = b.id AND m.account_id = $1))
+                 ORDER BY b.id",
+                &[&user_id],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
-        Ok(rows.into_iter().map(|r| row_to_board(&r)).collect())
+        rows.iter().map(row_to_board).collect()
     }
 
-    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Board>, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Board>, AppError> {
+        let client = self.client().await?;
 
         let rows = client
             .query(
                 "SELECT * FROM board WHERE id = $1 AND deleted = false",
-                &[&(board_id as i64)],
+                &[&board_id],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
-        Ok(rows.into_iter().map(|r| row_to_board(&r)).collect())
+        rows.iter().map(row_to_board).collect()
     }
 
-    async fn store(&self, entity: &Board) -> Result<i64, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn store(&self, entity: &Board) -> Result<i64, AppError> {
+        let client = self.client().await?;
 
         let row = client
             .query_one(
-                "INSERT INTO board (title, created_by) VALUES ($1, $2) RETURNING id",
-                &[&entity.title, &(entity.created_by as i64)],
+                "INSERT INTO board
+                     (title, created_by, anonymous, vote_budget, hide_votes, phase, author_salt)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 RETURNING id",
+                &[
+                    &entity.title,
+                    &entity.created_by,
+                    &entity.settings.anonymous,
+                    &entity.settings.vote_budget,
+                    &entity.settings.hide_votes,
+                    &entity.phase.as_str(),
+                    &entity.author_salt,
+                ],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
-        let id: i64 = row.get("id");
-        Ok(id as i64)
+        Ok(row.get("id"))
     }
 
-    async fn update(&self, entity: &Board) -> Result<(), String> {
+    async fn update(&self, entity: &Board) -> Result<Option<i64>, AppError> {
         if let Some(id) = entity.id {
-            let client = self.pool.get().await.map_err(|e| e.to_string())?;
-
-            client
-                .execute(
-                    "UPDATE board SET title = $1, updated_at = NOW() WHERE id = $2",
-                    &[&entity.title, &(id as i64)],
+            let client = self.client().await?;
+
+            let row_opt = client
+                .query_opt(
+                    "UPDATE board SET title = $1, version = version + 1, updated_at = NOW()
+                     WHERE id = $2 AND version = $3
+                     RETURNING version",
+                    &[&entity.title, &id, &entity.version],
                 )
-                .await
-                .map_err(|e| e.to_string())?;
+                .await?;
 
-            Ok(())
+            Ok(row_opt.map(|row| row.get("version")))
         } else {
-            Err("Board ID is not set".to_string())
+            Err(AppError::validation("Board ID is not set"))
         }
     }
 
-    async fn delete(&self, id: i64) -> Result<(), String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn update_settings(&self, entity: &Board) -> Result<(), AppError> {
+        let id = entity
+            .id
+            .ok_or_else(|| AppError::validation("Board ID is not set"))?;
+        let client = self.client().await?;
+
+        client
+            .execute(
+                "UPDATE board
+                 SET anonymous = $1, vote_budget = $2, hide_votes = $3, updated_at = NOW()
+                 WHERE id = $4",
+                &[
+                    &entity.settings.anonymous,
+                    &entity.settings.vote_budget,
+                    &entity.settings.hide_votes,
+                    &id,
+                ],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_phase(&self, entity: &Board) -> Result<(), AppError> {
+        let id = entity
+            .id
+            .ok_or_else(|| AppError::validation("Board ID is not set"))?;
+        let client = self.client().await?;
+
+        client
+            .execute(
+                "UPDATE board SET phase = $1, updated_at = NOW() WHERE id = $2",
+                &[&entity.phase.as_str(), &id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_creator(&self, entity: &Board) -> Result<(), AppError> {
+        let id = entity
+            .id
+            .ok_or_else(|| AppError::validation("Board ID is not set"))?;
+        let client = self.client().await?;
+
+        client
+            .execute(
+                "UPDATE board SET created_by = $1, updated_at = NOW() WHERE id = $2",
+                &[&entity.created_by, &id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), AppError> {
+        let client = self.client().await?;
 
         client
             .execute(
                 "UPDATE board SET deleted = TRUE, updated_at = NOW() WHERE id = $1",
-                &[&(id as i64)],
+                &[&id],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
         Ok(())
     }
-}
 
-fn row_to_board(row: &Row) -> Board {
-    let id: i64 = row.get("id");
-    let created_by: i64 = row.get("created_by");
+    async fn find_members(&self, board_id: i64) -> Result<Vec<BoardMember>, AppError> {
+        let client = self.client().await?;
 
-    Board::new(
-        Some(id as i64),
+        let rows = client
+            .query(
+                "SELECT * FROM board_member WHERE board_id = $1 ORDER BY created_at",
+                &[&board_id],
+            )
+            .await?;
+
+        rows.iter().map(row_to_board_member).collect()
+    }
+
+    async fn find_member_role(
+        &self,
+        board_id: i64,
+        account_id: i64,
+    ) -> Result<Option<BoardRole>, AppError> {
+        let client = self.client().await?;
+
+        let row_opt = client
+            .query_opt(
+                "SELECT role FROM board_member WHERE board_id = $1 AND account_id = $2",
+                &[&board_id, &account_id],
+            )
+            .await?;
+
+        row_opt.map(|row| parse_role(&row)).transpose()
+    }
+
+    async fn add_member(&self, member: &BoardMember) -> Result<(), AppError> {
+        let client = self.client().await?;
+
+        // 既にメンバーの場合はロールを更新
+        client
+            .execute(
+                "INSERT INTO board_member (board_id, account_id, role) VALUES ($1, $2, $3)
+                 ON CONFLICT (board_id, account_id) DO UPDATE SET role = EXCLUDED.role",
+                &[&member.board_id, &member.account_id, &member.role.as_str()],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_member(&self, board_id: i64, account_id: i64) -> Result<bool, AppError> {
+        let client = self.client().await?;
+
+        let deleted = client
+            .execute(
+                "DELETE FROM board_member WHERE board_id = $1 AND account_id = $2",
+                &[&board_id, &account_id],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn find_columns(&self, board_id: i64) -> Result<Vec<BoardColumn>, AppError> {
+        let client = self.client().await?;
+
+        let rows = client
+            .query(
+                "SELECT * FROM board_column WHERE board_id = $1 ORDER BY position",
+                &[&board_id],
+            )
+            .await?;
+
+        Ok(rows.iter().map(row_to_board_column).collect())
+    }
+
+    async fn add_columns(&self, columns: &[BoardColumn]) -> Result<(), AppError> {
+        let client = self.client().await?;
+
+        for column in columns {
+            client
+                .execute(
+                    "INSERT INTO board_column (board_id, category, position) VALUES ($1, $2, $3)",
+                    &[&column.board_id, &column.category, &column.position],
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn row_to_board(row: &Row) -> Result<Board, AppError> {
+    let mut board = Board::new(
+        Some(row.get("id")),
         row.get("title"),
-        created_by as i64,
+        row.get("created_by"),
         row.get("created_at"),
         row.get("updated_at"),
+    );
+    board.version = row.get("version");
+    board.settings.anonymous = row.get("anonymous");
+    board.settings.vote_budget = row.get("vote_budget");
+    board.settings.hide_votes = row.get("hide_votes");
+    board.phase = row
+        .get::<_, String>("phase")
+        .parse()
+        .map_err(AppError::Database)?;
+    board.author_salt = row.get("author_salt");
+    Ok(board)
+}
+
+fn row_to_board_member(row: &Row) -> Result<BoardMember, AppError> {
+    Ok(BoardMember::new(
+        row.get("board_id"),
+        row.get("account_id"),
+        parse_role(row)?,
+        row.get("created_at"),
+    ))
+}
+
+fn row_to_board_column(row: &Row) -> BoardColumn {
+    BoardColumn::new(
+        row.get("board_id"),
+        row.get("category"),
+        row.get("position"),
     )
 }
+
+fn parse_role(row: &Row) -> Result<BoardRole, AppError> {
+    row.get::<_, String>("role")
+        .parse()
+        .map_err(AppError::Database)
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use tokio_postgres::error::SqlState;
+
+use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
+use crate::error::AppError;
+use crate::repositories::identities::Identities;
+
+#[derive(Clone)]
+pub struct IdentitiesImpl {
+    pub pool: Arc<DbPool>,
+    pub tx: Option<UnitOfWork>,
+}
+
+impl IdentitiesImpl {
+    // 共有トランザクションに参加したリポジトリを返す
+    pub fn join(&self, tx: &UnitOfWork) -> IdentitiesImpl {
+        IdentitiesImpl {
+            pool: self.pool.clone(),
+            tx: Some(tx.clone()),
+        }
+    }
+
+    async fn client(&self) -> Result<DbClient<'_>, AppError> {
+        acquire(&self.pool, self.tx.as_ref()).await
+    }
+}
+
+#[axum::async_trait]
+impl Identities for IdentitiesImpl {
+    async fn find_account_id(&self, issuer: &str, subject: &str) -> Result<Option<i64>, AppError> {
+        let client = self.client().await?;
+
+        let row_opt = client
+            .query_opt(
+                "SELECT account_id FROM account_identity WHERE issuer = $1 AND subject = $2",
+                &[&issuer, &subject],
+            )
+            .await?;
+
+        Ok(row_opt.map(|row| row.get("account_id")))
+    }
+
+    async fn link(&self, issuer: &str, subject: &str, account_id: i64) -> Result<(), AppError> {
+        let client = self.client().await?;
+
+        client
+            .execute(
+                "INSERT INTO account_identity (issuer, subject, account_id) VALUES ($1, $2, $3)",
+                &[&issuer, &subject, &account_id],
+            )
+            .await
+            .map_err(|e| match e.code() {
+                Some(&SqlState::UNIQUE_VIOLATION) => {
+                    AppError::conflict("External identity is already linked")
+                }
+                _ => e.into(),
+            })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::database::DbPool;
+use crate::error::AppError;
+use crate::repositories::login_attempts::{Attempts, LoginAttempts};
+
+// 複数インスタンスで制限を共有する場合の Postgres 実装
+#[derive(Clone)]
+pub struct LoginAttemptsImpl {
+    pub pool: Arc<DbPool>,
+}
+
+#[axum::async_trait]
+impl LoginAttempts for LoginAttemptsImpl {
+    async fn get(&self, key: &str) -> Result<Option<Attempts>, AppError> {
+        let client = self.pool.get().await?;
+
+        let row_opt = client
+            .query_opt(
+                "SELECT failures, locked_until, updated_at FROM login_attempt WHERE key = $1",
+                &[&key],
+            )
+            .await?;
+
+        Ok(row_opt.map(|row| Attempts {
+            failures: row.get("failures"),
+            locked_until: row.get("locked_until"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    async fn increment(&self, key: &str, window: Duration) -> Result<i32, AppError> {
+        let client = self.pool.get().await?;
+
+        let stale_before = Utc::now() - window;
+        let row = client
+            .query_one(
+                "INSERT INTO login_attempt (key, failures) VALUES ($1, 1)
+                 ON CONFLICT (key) DO UPDATE SET
+                     failures = CASE WHEN login_attempt.updated_at < $2 THEN 1
+                                     ELSE login_attempt.failures + 1 END,
+                     updated_at = NOW()
+                 RETURNING failures",
+                &[&key, &stale_before],
+            )
+            .await?;
+
+        Ok(row.get("failures"))
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+
+        client
+            .execute(
+                "UPDATE login_attempt SET locked_until = $2 WHERE key = $1",
+                &[&key, &until],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+
+        client
+            .execute("DELETE FROM login_attempt WHERE key = $1", &[&key])
+            .await?;
+
+        Ok(())
+    }
+}
+
+// 既定のインスタンス内メモリ実装
+#[derive(Clone, Default)]
+pub struct MemoryLoginAttempts {
+    entries: Arc<Mutex<HashMap<String, Attempts>>>,
+}
+
+// これを超えたら古いエントリを掃除する
+const MEMORY_PRUNE_THRESHOLD: usize = 10_000;
+
+impl MemoryLoginAttempts {
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, Attempts>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[axum::async_trait]
+impl LoginAttempts for MemoryLoginAttempts {
+    async fn get(&self, key: &str) -> Result<Option<Attempts>, AppError> {
+        Ok(self.entries().get(key).copied())
+    }
+
+    async fn increment(&self, key: &str, window: Duration) -> Result<i32, AppError> {
+        let now = Utc::now();
+        let mut entries = self.entries();
+
+        if entries.len() > MEMORY_PRUNE_THRESHOLD {
+            entries.retain(|_, a| {
+                a.updated_at >= now - window || a.locked_until.is_some_and(|until| until > now)
+            });
+        }
+
+        let attempts = entries.entry(key.to_string()).or_insert(Attempts {
+            failures: 0,
+            locked_until: None,
+            updated_at: now,
+        });
+        if attempts.updated_at < now - window {
+            attempts.failures = 0;
+        }
+        attempts.failures += 1;
+        attempts.updated_at = now;
+
+        Ok(attempts.failures)
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), AppError> {
+        if let Some(attempts) = self.entries().get_mut(key) {
+            attempts.locked_until = Some(until);
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        self.entries().remove(key);
+        Ok(())
+    }
+}
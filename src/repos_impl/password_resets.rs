@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use crate::database::DbPool;
+use crate::entities::PasswordResetToken;
+use crate::error::AppError;
+use crate::repositories::password_resets::PasswordResets;
+
+#[derive(Clone)]
+pub struct PasswordResetsImpl {
+    pub pool: Arc<DbPool>,
+}
+
+#[axum::async_trait]
+impl PasswordResets for PasswordResetsImpl {
+    async fn store(&self, entity: &PasswordResetToken) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+
+        client
+            .execute(
+                "INSERT INTO password_reset_token (token_hash, account_id, expires_at)
+                 VALUES ($1, $2, $3)",
+                &[&entity.token_hash, &entity.account_id, &entity.expires_at],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, token_hash: &str) -> Result<Option<i64>, AppError> {
+        let client = self.pool.get().await?;
+
+        // 条件付き UPDATE で同じトークンの二重使用を防ぐ
+        let row_opt = client
+            .query_opt(
+                "UPDATE password_reset_token SET used_at = NOW()
+                 WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+                 RETURNING account_id",
+                &[&token_hash],
+            )
+            .await?;
+
+        Ok(row_opt.map(|row| row.get("account_id")))
+    }
+}
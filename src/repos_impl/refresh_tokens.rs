@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use tokio_postgres::Row;
+
+use crate::database::DbPool;
+use crate::entities::{LoginSession, RefreshToken};
+use crate::error::AppError;
+use crate::repositories::refresh_tokens::RefreshTokens;
+
+#[derive(Clone)]
+pub struct RefreshTokensImpl {
+    pub pool: Arc<DbPool>,
+}
+
+#[axum::async_trait]
+impl RefreshTokens for RefreshTokensImpl {
+    async fn find(&self, token_hash: &str) -> Result<Option<RefreshToken>, AppError> {
+        let client = self.pool.get().await?;
+
+        let row_opt = client
+            .query_opt(
+                "SELECT * FROM refresh_token WHERE token_hash = $1",
+                &[&token_hash],
+            )
+            .await?;
+
+        Ok(row_opt.map(|row| row_to_refresh_token(&row)))
+    }
+
+    async fn store(&self, entity: &RefreshToken) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+
+        client
+            .execute(
+                "INSERT INTO refresh_token (token_hash, family_id, account_id, expires_at)
+                 VALUES ($1, $2, $3, $4)",
+                &[
+                    &entity.token_hash,
+                    &entity.family_id,
+                    &entity.account_id,
+                    &entity.expires_at,
+                ],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn mark_used(&self, token_hash: &str) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+
+        let updated = client
+            .execute(
+                "UPDATE refresh_token SET used_at = NOW()
+                 WHERE token_hash = $1 AND used_at IS NULL",
+                &[&token_hash],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+
+    async fn find_families(&self, account_id: i64) -> Result<Vec<LoginSession>, AppError> {
+        let client = self.pool.get().await?;
+
+        let rows = client
+            .query(
+                "SELECT family_id, MIN(created_at) AS created_at, MAX(expires_at) AS expires_at
+                 FROM refresh_token WHERE account_id = $1
+                 GROUP BY family_id
+                 HAVING BOOL_OR(used_at IS NULL AND expires_at > NOW())
+                 ORDER BY created_at",
+                &[&account_id],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| LoginSession {
+                id: row.get("family_id"),
+                account_id,
+                created_at: row.get("created_at"),
+                expires_at: row.get("expires_at"),
+                user_agent: None,
+            })
+            .collect())
+    }
+
+    async fn delete_family(&self, account_id: i64, family_id: &str) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+
+        let deleted = client
+            .execute(
+                "DELETE FROM refresh_token WHERE account_id = $1 AND family_id = $2",
+                &[&account_id, &family_id],
+            )
+            .await?;
+
+        Ok(deleted)
+    }
+
+    async fn delete_by_account(&self, account_id: i64) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+
+        let deleted = client
+            .execute(
+                "DELETE FROM refresh_token WHERE account_id = $1",
+                &[&account_id],
+            )
+            .await?;
+
+        Ok(deleted)
+    }
+}
+
+fn row_to_refresh_token(row: &Row) -> RefreshToken {
+    RefreshToken {
+        token_hash: row.get("token_hash"),
+        family_id: row.get("family_id"),
+        account_id: row.get("account_id"),
+        expires_at: row.get("expires_at"),
+        used_at: row.get("used_at"),
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use async_session::Session;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Row;
+
+use crate::constants::{
+    AXUM_SESSION_CREATED_AT_KEY, AXUM_SESSION_USER_AGENT_KEY, AXUM_SESSION_USER_ID_KEY,
+};
+use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
+use crate::entities::LoginSession;
+use crate::error::AppError;
+use crate::repositories::sessions::Sessions;
+
+// async_sessions テーブル（PostgresSessionStore の保存先）を直接参照する
+// ストア自体にはユーザー単位で一覧・削除する手段がないため
+#[derive(Clone)]
+pub struct SessionsImpl {
+    pub pool: Arc<DbPool>,
+    pub tx: Option<UnitOfWork>,
+}
+
+impl SessionsImpl {
+    // 共有トランザクションに参加したリポジトリを返す
+    pub fn join(&self, tx: &UnitOfWork) -> SessionsImpl {
+        SessionsImpl {
+            pool: self.pool.clone(),
+            tx: Some(tx.clone()),
+        }
+    }
+
+    async fn client(&self) -> Result<DbClient<'_>, AppError> {
+        acquire(&self.pool, self.tx.as_ref()).await
+    }
+}
+
+#[axum::async_trait]
+impl Sessions for SessionsImpl {
+    async fn find_by_account(&self, account_id: i64) -> Result<Vec<LoginSession>, AppError> {
+        let conn = self.client().await?;
+
+        // セッションの値は JSON 文字列として保存されている
+        let rows = conn
+            .query(
+                "SELECT id, session, expires FROM async_sessions
+                 WHERE (expires IS NULL OR expires > NOW())
+                   AND session::jsonb -> 'data' ->> $1::text = $2",
+                &[&AXUM_SESSION_USER_ID_KEY, &account_id.to_string()],
+            )
+            .await?;
+
+        // 新しいものから順に返す
+        let mut sessions = rows
+            .iter()
+            .map(|row| row_to_login_session(row, account_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+        Ok(sessions)
+    }
+
+    async fn delete(&self, account_id: i64, session_id: &str) -> Result<bool, AppError> {
+        let conn = self.client().await?;
+
+        let deleted = conn
+            .execute(
+                "DELETE FROM async_sessions
+                 WHERE id = $1 AND session::jsonb -> 'data' ->> $2::text = $3",
+                &[
+                    &session_id,
+                    &AXUM_SESSION_USER_ID_KEY,
+                    &account_id.to_string(),
+                ],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn delete_by_account(&self, account_id: i64) -> Result<u64, AppError> {
+        let conn = self.client().await?;
+
+        let deleted = conn
+            .execute(
+                "DELETE FROM async_sessions WHERE session::jsonb -> 'data' ->> $1::text = $2",
+                &[&AXUM_SESSION_USER_ID_KEY, &account_id.to_string()],
+            )
+            .await?;
+
+        Ok(deleted)
+    }
+}
+
+fn row_to_login_session(row: &Row, account_id: i64) -> Result<LoginSession, AppError> {
+    let session: Session = serde_json::from_str(row.get("session"))
+        .map_err(|e| AppError::Database(format!("Invalid session data: {}", e)))?;
+
+    Ok(LoginSession {
+        id: row.get("id"),
+        account_id,
+        created_at: session.get::<DateTime<Utc>>(AXUM_SESSION_CREATED_AT_KEY),
+        expires_at: row.get("expires"),
+        user_agent: session.get::<String>(AXUM_SESSION_USER_AGENT_KEY),
+    })
+}
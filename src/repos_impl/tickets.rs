@@ -1,6 +1,6 @@
 // use sqlx::{Row, mysql::MySqlRow};
 
-// use crate::database::DbPool;
+// use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
 // use crate::entities::Ticket;
 // use crate::repositories::tickets::Tickets;
 
@@ -91,119 +91,242 @@
 //     }
 // }
 
-use bb8::Pool;
-use bb8_postgres::PostgresConnectionManager;
 use std::sync::Arc;
-use tokio_postgres::{NoTls, Row, types::ToSql};
+use tokio_postgres::Row;
 
-use crate::database::DbPool;
-use crate::entities::Ticket;
+use crate::database::{DbClient, DbPool, UnitOfWork, acquire};
+use crate::entities::{Ticket, TicketVote};
+use crate::error::AppError;
 use crate::repositories::tickets::Tickets;
-use anyhow::Result;
 
 #[derive(Clone)]
 pub struct TicketsImpl {
-    pub pool: Arc<Pool<PostgresConnectionManager<NoTls>>>,
+    pub pool: Arc<DbPool>,
+    pub tx: Option<UnitOfWork>,
+}
+
+impl TicketsImpl {
+    // 共有トランザクションに参加したリポジトリを返す
+    pub fn join(&self, tx: &UnitOfWork) -> TicketsImpl {
+        TicketsImpl {
+            pool: self.pool.clone(),
+            tx: Some(tx.clone()),
+        }
+    }
+
+    async fn client(&self) -> Result<DbClient<'_>, AppError> {
+        acquire(&self.pool, self.tx.as_ref()).await
+    }
 }
 
 #[axum::async_trait]
 impl Tickets for TicketsImpl {
-    async fn find(&self, id: i64) -> Option<Ticket> {
-        let client = self.pool.get().await.ok()?;
+    async fn find(&self, id: i64) -> Result<Option<Ticket>, AppError> {
+        let client = self.client().await?;
 
-        let row = client
+        let row_opt = client
             .query_opt(
                 "SELECT * FROM ticket WHERE id = $1 AND deleted = FALSE",
-                &[&(id as i64)],
+                &[&id],
             )
-            .await
-            .ok()??;
+            .await?;
 
-        Some(row_to_ticket(&row))
+        Ok(row_opt.map(|row| row_to_ticket(&row)))
     }
 
-    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Ticket>, String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Ticket>, AppError> {
+        let client = self.client().await?;
         let rows = client
             .query(
-                "SELECT * FROM ticket WHERE board_id = $1 AND deleted = FALSE",
-                &[&(board_id as i64)],
+                "SELECT * FROM ticket WHERE board_id = $1 AND deleted = FALSE ORDER BY position, id",
+                &[&board_id],
             )
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
         Ok(rows.into_iter().map(|row| row_to_ticket(&row)).collect())
     }
 
-    async fn store(&self, entity: &Ticket) -> Result<(), String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
+    // チケットへの書き込みはボードの版も進める（ボード全体の保存との競合検出用）
+    async fn store(&self, entity: &Ticket) -> Result<i64, AppError> {
+        let client = self.client().await?;
 
-        let result = client
-            .execute(
-                "INSERT INTO ticket (board_id, author_id, category, content) VALUES ($1, $2, $3, $4)",
+        let row = client
+            .query_one(
+                "WITH inserted AS (
+                     INSERT INTO ticket (board_id, author_id, category, content, anonymous, position)
+                     VALUES ($1, $2, $3, $4, $5, (
+                         SELECT COALESCE(MAX(position) + 1, 0) FROM ticket
+                         WHERE board_id = $1 AND category = $3 AND deleted = FALSE
+                     ))
+                     RETURNING id, board_id
+                 ), touched AS (
+                     UPDATE board SET version = version + 1, updated_at = NOW()
+                     WHERE id IN (SELECT board_id FROM inserted)
+                 )
+                 SELECT id FROM inserted",
                 &[
-                    &(entity.board_id as i64),
-                    &(entity.author_id as i64),
+                    &entity.board_id,
+                    &entity.author_id,
                     &entity.category,
                     &entity.content,
+                    &entity.anonymous,
                 ],
             )
-            .await;
+            .await?;
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!("Failed to store ticket: {}", e)),
-        }
+        Ok(row.get("id"))
     }
 
-    async fn update(&self, entity: &Ticket) -> Result<(), String> {
+    async fn update(&self, entity: &Ticket) -> Result<Option<i64>, AppError> {
         if let Some(id) = entity.id {
-            let client = self.pool.get().await.map_err(|e| e.to_string())?;
+            let client = self.client().await?;
 
-            let result = client
-                .execute(
-                    "UPDATE ticket SET category = $1, content = $2, updated_at = NOW() WHERE id = $3",
-                    &[&entity.category, &entity.content, &(id as i64)],
+            let row_opt = client
+                .query_opt(
+                    "WITH updated AS (
+                         UPDATE ticket
+                         SET position = CASE WHEN category = $1 THEN position ELSE (
+                                 SELECT COALESCE(MAX(t.position) + 1, 0) FROM ticket t
+                                 WHERE t.board_id = ticket.board_id AND t.category = $1
+                                   AND t.deleted = FALSE
+                             ) END,
+                             category = $1, content = $2, version = version + 1, updated_at = NOW()
+                         WHERE id = $3 AND version = $4
+                         RETURNING version, board_id
+                     ), touched AS (
+                         UPDATE board SET version = version + 1, updated_at = NOW()
+                         WHERE id IN (SELECT board_id FROM updated)
+                     )
+                     SELECT version FROM updated",
+                    &[&entity.category, &entity.content, &id, &entity.version],
                 )
-                .await;
+                .await?;
 
-            match result {
-                Ok(_) => Ok(()),
-                Err(e) => Err(format!("Failed to update ticket: {}", e)),
-            }
+            Ok(row_opt.map(|row| row.get("version")))
         } else {
-            Err("Ticket ID is not set".to_string())
+            Err(AppError::validation("Ticket ID is not set"))
         }
     }
 
-    async fn delete(&self, id: i64) -> Result<(), String> {
-        let client = self.pool.get().await.map_err(|e| e.to_string())?;
-        let result = client
+    async fn delete(&self, id: i64) -> Result<(), AppError> {
+        let client = self.client().await?;
+        client
             .execute(
-                "UPDATE ticket SET deleted = TRUE, updated_at = NOW() WHERE id = $1",
-                &[&(id as i64)],
+                "WITH deleted AS (
+                     UPDATE ticket SET deleted = TRUE, version = version + 1, updated_at = NOW()
+                     WHERE id = $1
+                     RETURNING board_id
+                 )
+                 UPDATE board SET version = version + 1, updated_at = NOW()
+                 WHERE id IN (SELECT board_id FROM deleted)",
+                &[&id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn reorder(&self, ids: &[i64]) -> Result<(), AppError> {
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE ticket SET position = array_position($1::BIGINT[], id) - 1
+                 WHERE id = ANY($1)",
+                &[&ids],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_votes_by_board_id(&self, board_id: i64) -> Result<Vec<TicketVote>, AppError> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                "SELECT v.* FROM ticket_vote v JOIN ticket t ON t.id = v.ticket_id
+                 WHERE t.board_id = $1 AND t.deleted = FALSE
+                 ORDER BY v.id",
+                &[&board_id],
             )
-            .await;
+            .await?;
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!("Failed to delete ticket: {}", e)),
+        Ok(rows.iter().map(row_to_ticket_vote).collect())
+    }
+
+    async fn add_vote(&self, vote: &TicketVote, budget: i32) -> Result<bool, AppError> {
+        // ロックはトランザクションの終了まで保持されるため、単独で呼ばれた場合は自前で開始する
+        if self.tx.is_none() {
+            let tx = UnitOfWork::begin(&self.pool).await?;
+            let inserted = self.join(&tx).add_vote(vote, budget).await?;
+            tx.commit().await?;
+            return Ok(inserted);
         }
+        let client = self.client().await?;
+
+        // 同じボードへの同じ投票者の投票を直列化する
+        // （READ COMMITTED では同時に数えた票数がどちらも上限未満になりうるため）
+        client
+            .execute(
+                "SELECT pg_advisory_xact_lock(
+                     hashtextextended('ticket_vote:' || board_id || ':' || $2::BIGINT, 0)
+                 ) FROM ticket WHERE id = $1",
+                &[&vote.ticket_id, &vote.account_id],
+            )
+            .await?;
+
+        // ロックの取得後に票数を数え、上限未満の場合のみ追加する
+        let inserted = client
+            .execute(
+                "INSERT INTO ticket_vote (ticket_id, account_id)
+                 SELECT $1, $2
+                 WHERE (
+                     SELECT COUNT(*) FROM ticket_vote v JOIN ticket t ON t.id = v.ticket_id
+                     WHERE v.account_id = $2 AND t.deleted = FALSE
+                       AND t.board_id = (SELECT board_id FROM ticket WHERE id = $1)
+                 ) < $3",
+                &[&vote.ticket_id, &vote.account_id, &i64::from(budget)],
+            )
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    async fn remove_vote(&self, ticket_id: i64, account_id: i64) -> Result<bool, AppError> {
+        let client = self.client().await?;
+        let deleted = client
+            .execute(
+                "DELETE FROM ticket_vote WHERE id = (
+                     SELECT id FROM ticket_vote WHERE ticket_id = $1 AND account_id = $2
+                     ORDER BY id DESC LIMIT 1
+                 )",
+                &[&ticket_id, &account_id],
+            )
+            .await?;
+
+        Ok(deleted > 0)
     }
 }
 
 fn row_to_ticket(row: &Row) -> Ticket {
-    let id: i64 = row.get("id");
-    let board_id: i64 = row.get("board_id");
-    let author_id: i64 = row.get("author_id");
-
-    Ticket::new(
-        Some(id as i64),
-        board_id as i64,
-        author_id as i64,
+    let mut ticket = Ticket::new(
+        Some(row.get("id")),
+        row.get("board_id"),
+        row.get("author_id"),
         row.get("category"),
         row.get("content"),
         row.get("created_at"),
         row.get("updated_at"),
+    );
+    ticket.version = row.get("version");
+    ticket.position = row.get("position");
+    ticket.anonymous = row.get("anonymous");
+    ticket
+}
+
+fn row_to_ticket_vote(row: &Row) -> TicketVote {
+    TicketVote::new(
+        row.get("ticket_id"),
+        row.get("account_id"),
+        row.get("created_at"),
     )
 }
@@ -1,9 +1,15 @@
-use std::collections::{HashMap, HashSet};
 use crate::entities::Account;
+use crate::error::AppError;
+use std::collections::{HashMap, HashSet};
 
 #[axum::async_trait]
 pub trait Accounts {
-    async fn find(&self, ids: HashSet<i64>) -> HashMap<i64, Account>;
-    async fn find_by(&self, display_name: &str) -> Option<Account>;
-    async fn store(&self, entity: &Account);
-}
\ No newline at end of file
+    async fn find(&self, ids: HashSet<i64>) -> Result<HashMap<i64, Account>, AppError>;
+    // 削除済みのアカウントは含まない
+    async fn find_by(&self, display_name: &str) -> Result<Option<Account>, AppError>;
+    async fn store(&self, entity: &Account) -> Result<i64, AppError>;
+    async fn update_password(&self, entity: &Account) -> Result<(), AppError>;
+    async fn update_display_name(&self, entity: &Account) -> Result<(), AppError>;
+    // 匿名化した内容で更新し、ログインに使える資格情報をすべて削除する
+    async fn anonymize(&self, entity: &Account) -> Result<(), AppError>;
+}
@@ -0,0 +1,14 @@
+use crate::entities::ActionItem;
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait ActionItems {
+    async fn find(&self, id: i64) -> Result<Option<ActionItem>, AppError>;
+    // 期限の近い順
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<ActionItem>, AppError>;
+    // 削除済みのボードや、担当者がメンバーでなくなったボードのものは含めない
+    async fn find_by_assignee(&self, account_id: i64) -> Result<Vec<ActionItem>, AppError>;
+    async fn store(&self, entity: &ActionItem) -> Result<i64, AppError>;
+    async fn update(&self, entity: &ActionItem) -> Result<(), AppError>;
+    async fn delete(&self, id: i64) -> Result<(), AppError>;
+}
@@ -0,0 +1,11 @@
+use crate::entities::ApiToken;
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait ApiTokens {
+    async fn store(&self, entity: &ApiToken) -> Result<i64, AppError>;
+    async fn find_by_account(&self, account_id: i64) -> Result<Vec<ApiToken>, AppError>;
+    // 期限内であれば最終使用日時を更新して返す
+    async fn use_token(&self, token_hash: &str) -> Result<Option<ApiToken>, AppError>;
+    async fn delete(&self, account_id: i64, id: i64) -> Result<bool, AppError>;
+}
@@ -1,14 +1,30 @@
-use crate::entities::Board;
+use crate::entities::{Board, BoardColumn, BoardMember, BoardRole};
+use crate::error::AppError;
 
-#[axum::async_trait]
 #[axum::async_trait]
 pub trait Boards {
-    async fn find(&self, id: i64) -> Result<Option<Board>, String>;
-    async fn find_by_title(&self, title: &str) -> Result<Vec<Board>, String>;
-    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Board>, String>;
-    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Board>, String>;
-    async fn store(&self, entity: &Board) -> Result<i64, String>;
-    async fn update(&self, entity: &Board) -> Result<(), String>;
-    async fn delete(&self, id: i64) -> Result<(), String>;
+    async fn find(&self, id: i64) -> Result<Option<Board>, AppError>;
+    async fn find_by_title(&self, title: &str) -> Result<Vec<Board>, AppError>;
+    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<Board>, AppError>;
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Board>, AppError>;
+    async fn store(&self, entity: &Board) -> Result<i64, AppError>;
+    // entity.version が現在の版と一致した場合のみ更新し、新しい版を返す（不一致なら None）
+    async fn update(&self, entity: &Board) -> Result<Option<i64>, AppError>;
+    async fn update_settings(&self, entity: &Board) -> Result<(), AppError>;
+    async fn update_phase(&self, entity: &Board) -> Result<(), AppError>;
+    // 作成者（created_by）の付け替え
+    async fn update_creator(&self, entity: &Board) -> Result<(), AppError>;
+    async fn delete(&self, id: i64) -> Result<(), AppError>;
+    async fn find_members(&self, board_id: i64) -> Result<Vec<BoardMember>, AppError>;
+    async fn find_member_role(
+        &self,
+        board_id: i64,
+        account_id: i64,
+    ) -> Result<Option<BoardRole>, AppError>;
+    async fn add_member(&self, member: &BoardMember) -> Result<(), AppError>;
+    // メンバーでなければ false
+    async fn remove_member(&self, board_id: i64, account_id: i64) -> Result<bool, AppError>;
+    // position の昇順
+    async fn find_columns(&self, board_id: i64) -> Result<Vec<BoardColumn>, AppError>;
+    async fn add_columns(&self, columns: &[BoardColumn]) -> Result<(), AppError>;
 }
-
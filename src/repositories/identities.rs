@@ -0,0 +1,8 @@
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait Identities {
+    // 外部IdPの subject に紐付いたアカウントID
+    async fn find_account_id(&self, issuer: &str, subject: &str) -> Result<Option<i64>, AppError>;
+    async fn link(&self, issuer: &str, subject: &str, account_id: i64) -> Result<(), AppError>;
+}
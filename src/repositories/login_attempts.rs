@@ -0,0 +1,20 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::AppError;
+
+// キー（表示名・IPアドレス）ごとのログイン失敗状況
+#[derive(Debug, Clone, Copy)]
+pub struct Attempts {
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[axum::async_trait]
+pub trait LoginAttempts: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Attempts>, AppError>;
+    // 失敗を1件加えて新しい失敗回数を返す（最後の失敗から window 以上経っていれば数え直す）
+    async fn increment(&self, key: &str, window: Duration) -> Result<i32, AppError>;
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), AppError>;
+    async fn clear(&self, key: &str) -> Result<(), AppError>;
+}
@@ -1,3 +1,10 @@
 pub mod accounts;
+pub mod action_items;
+pub mod api_tokens;
 pub mod boards;
+pub mod identities;
+pub mod login_attempts;
+pub mod password_resets;
+pub mod refresh_tokens;
+pub mod sessions;
 pub mod tickets;
\ No newline at end of file
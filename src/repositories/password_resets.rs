@@ -0,0 +1,9 @@
+use crate::entities::PasswordResetToken;
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait PasswordResets {
+    async fn store(&self, entity: &PasswordResetToken) -> Result<(), AppError>;
+    // 未使用かつ期限内であれば使用済みにしてアカウントIDを返す
+    async fn consume(&self, token_hash: &str) -> Result<Option<i64>, AppError>;
+}
@@ -0,0 +1,14 @@
+use crate::entities::{LoginSession, RefreshToken};
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait RefreshTokens {
+    async fn find(&self, token_hash: &str) -> Result<Option<RefreshToken>, AppError>;
+    async fn store(&self, entity: &RefreshToken) -> Result<(), AppError>;
+    // 未使用の場合のみ使用済みにする（同時に使われた場合は片方が false）
+    async fn mark_used(&self, token_hash: &str) -> Result<bool, AppError>;
+    // 有効なトークンが残っている系列をログイン中のセッションとして返す（id は系列ID）
+    async fn find_families(&self, account_id: i64) -> Result<Vec<LoginSession>, AppError>;
+    async fn delete_family(&self, account_id: i64, family_id: &str) -> Result<u64, AppError>;
+    async fn delete_by_account(&self, account_id: i64) -> Result<u64, AppError>;
+}
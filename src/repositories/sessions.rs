@@ -0,0 +1,11 @@
+use crate::entities::LoginSession;
+use crate::error::AppError;
+
+#[axum::async_trait]
+pub trait Sessions {
+    async fn find_by_account(&self, account_id: i64) -> Result<Vec<LoginSession>, AppError>;
+    // 該当するセッションがなければ false
+    async fn delete(&self, account_id: i64, session_id: &str) -> Result<bool, AppError>;
+    // アカウントのセッションをすべて削除し、削除した件数を返す
+    async fn delete_by_account(&self, account_id: i64) -> Result<u64, AppError>;
+}
@@ -1,10 +1,20 @@
-use crate::entities::Ticket;
+use crate::entities::{Ticket, TicketVote};
+use crate::error::AppError;
 
 #[axum::async_trait]
 pub trait Tickets {
-    async fn find(&self, id: i64) -> Option<Ticket>;
-    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Ticket>, String>;
-    async fn store(&self, entity: &Ticket) -> Result<(), String>;
-    async fn update(&self, entity: &Ticket) -> Result<(), String>;
-    async fn delete(&self, id: i64) -> Result<(), String>;
+    async fn find(&self, id: i64) -> Result<Option<Ticket>, AppError>;
+    async fn find_by_board_id(&self, board_id: i64) -> Result<Vec<Ticket>, AppError>;
+    async fn store(&self, entity: &Ticket) -> Result<i64, AppError>;
+    // entity.version が現在の版と一致した場合のみ更新し、新しい版を返す（不一致なら None）
+    async fn update(&self, entity: &Ticket) -> Result<Option<i64>, AppError>;
+    async fn delete(&self, id: i64) -> Result<(), AppError>;
+    // ids の順に並び順を振り直す
+    async fn reorder(&self, ids: &[i64]) -> Result<(), AppError>;
+    // 削除済みチケットへの票は含めない
+    async fn find_votes_by_board_id(&self, board_id: i64) -> Result<Vec<TicketVote>, AppError>;
+    // ボード内の投票者の票数が budget 未満の場合のみ追加する（追加できなければ false）
+    async fn add_vote(&self, vote: &TicketVote, budget: i32) -> Result<bool, AppError>;
+    // 投票者の票を1つ取り消す（票がなければ false）
+    async fn remove_vote(&self, ticket_id: i64, account_id: i64) -> Result<bool, AppError>;
 }
@@ -1,60 +1,177 @@
-use std::env;
-
 use async_session::SessionStore;
-use async_sqlx_session::PostgresSessionStore; 
+use async_sqlx_session::PostgresSessionStore;
 use axum::{
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
-    Json,
+    extract::{FromRef, FromRequest, FromRequestParts},
+    http::request::Parts,
+    response::{IntoResponse, Response},
 };
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use axum_extra::headers::{Authorization, authorization::Bearer};
 use axum_extra::TypedHeader;
+use axum_extra::headers::{Authorization, authorization::Bearer};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::auth::AuthMode;
+use crate::constants::AXUM_SESSION_USER_ID_KEY;
+use crate::database::Repositories;
+use crate::entities::{API_TOKEN_PREFIX, ApiScope, ApiToken};
+use crate::error::AppError;
+use crate::repositories::api_tokens::ApiTokens;
 
-use crate::constants::{AXUM_SESSION_COOKIE_NAME, AXUM_SESSION_USER_ID_KEY, ENV_KEY_DATABASE_URL};
+// axum の Json・Path・Query と同じだが、拒否された場合は AppError で返す
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct Path<T>(pub T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct Query<T>(pub T);
 
 #[derive(Deserialize, Serialize)]
 pub struct UserContext {
     pub user_id: i64,
+    // 認証に使われたセッションのストア上のID（JWTモードではリフレッシュトークンの系列ID、
+    // 個人用トークンではトークンのID）
+    pub session_id: String,
+    // 個人用トークンで認証された場合のスコープ（ログインによる認証では None）
+    pub scopes: Option<Vec<ApiScope>>,
+}
+
+impl UserContext {
+    pub fn has_scope(&self, required: ApiScope) -> bool {
+        match &self.scopes {
+            Some(scopes) => scopes.iter().any(|scope| scope.grants(required)),
+            None => true,
+        }
+    }
+
+    // トークン・セッション・パスワードの管理はログインした本人のみ
+    pub fn require_login(&self) -> Result<(), AppError> {
+        match self.scopes {
+            Some(_) => Err(AppError::Forbidden(
+                "This operation is not available with an API token".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
 }
 
 #[axum::async_trait]
 impl<S> FromRequestParts<S> for UserContext
 where
     S: Send + Sync,
+    PostgresSessionStore: FromRef<S>,
+    AuthMode: FromRef<S>,
+    Arc<Repositories>: FromRef<S>,
 {
-    type Rejection = (StatusCode, Json<serde_json::Value>);
+    type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let error_response = || {
-            (
-                StatusCode::UNAUTHORIZED,
-                Json(json!({ "error": "Unauthorized" })),
-            )
-        };
-
-        dotenv::dotenv().ok();
-        let database_url = env::var(ENV_KEY_DATABASE_URL).unwrap_or_default();
-
-        // PostgreSQL用ストアを作成
-        let store = PostgresSessionStore::new(&database_url)
-            .await
-            .map_err(|_| error_response())?;
-
         // AuthorizationヘッダーからBearerトークン取得
         let TypedHeader(Authorization(bearer)) =
             TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
                 .await
-                .map_err(|_| error_response())?;
+                .map_err(|_| AppError::Unauthorized("Unauthorized".to_string()))?;
+
+        UserContext::authenticate(bearer.token().to_string(), state).await
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: String,
+}
 
-        let token_str = bearer.token();
+// WebSocket用の認証（ブラウザはヘッダーを付けられないため ?token= も受け付ける）
+// URLにトークンが残るため、このエクストラクタはWebSocketのエンドポイント以外で使わない
+pub struct WsUserContext(pub UserContext);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for WsUserContext
+where
+    S: Send + Sync,
+    PostgresSessionStore: FromRef<S>,
+    AuthMode: FromRef<S>,
+    Arc<Repositories>: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token =
+            match TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await {
+                Ok(TypedHeader(Authorization(bearer))) => bearer.token().to_string(),
+                Err(_) => {
+                    let axum::extract::Query(query) =
+                        axum::extract::Query::<TokenQuery>::try_from_uri(&parts.uri)
+                            .map_err(|_| AppError::Unauthorized("Unauthorized".to_string()))?;
+                    query.token
+                }
+            };
+
+        Ok(WsUserContext(
+            UserContext::authenticate(token, state).await?,
+        ))
+    }
+}
+
+impl UserContext {
+    // トークンの種類に応じて認証する
+    async fn authenticate<S>(token: String, state: &S) -> Result<UserContext, AppError>
+    where
+        S: Send + Sync,
+        PostgresSessionStore: FromRef<S>,
+        AuthMode: FromRef<S>,
+        Arc<Repositories>: FromRef<S>,
+    {
+        let error_response = || AppError::Unauthorized("Unauthorized".to_string());
+
+        // 個人用トークンは認証方式の設定にかかわらず受け付ける
+        if token.starts_with(API_TOKEN_PREFIX) {
+            let repos = Arc::<Repositories>::from_ref(state);
+            let api_token = repos
+                .api_tokens
+                .use_token(&ApiToken::hash(&token))
+                .await?
+                .ok_or_else(error_response)?;
+            return Ok(UserContext {
+                user_id: api_token.account_id,
+                session_id: api_token.id.unwrap_or_default().to_string(),
+                scopes: Some(api_token.scopes),
+            });
+        }
+
+        // JWTモードでは署名を検証するだけでDBは参照しない
+        if let AuthMode::Jwt(keys) = AuthMode::from_ref(state) {
+            let claims = keys.decode(&token)?;
+            return Ok(UserContext {
+                user_id: claims.sub.parse().map_err(|_| error_response())?,
+                session_id: claims.sid,
+                scopes: None,
+            });
+        }
+
+        // ストアは base64 以外の値をエラーにするため、先に形式で弾く
+        if STANDARD.decode(&token).is_err() {
+            return Err(error_response());
+        }
 
         // セッションロード
+        let store = PostgresSessionStore::from_ref(state);
         let session = store
-            .load_session(token_str.to_string())
+            .load_session(token)
             .await
-            .map_err(|_| error_response())?
+            .map_err(|e| AppError::Database(e.to_string()))?
             .ok_or_else(error_response)?;
 
         // user_id取得
@@ -62,6 +179,85 @@ where
             .get::<i64>(AXUM_SESSION_USER_ID_KEY)
             .ok_or_else(error_response)?;
 
-        Ok(UserContext { user_id })
+        Ok(UserContext {
+            user_id,
+            session_id: session.id().to_string(),
+            scopes: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    // 不正な形式のトークンではストアもリポジトリも参照されない
+    struct TestState;
+
+    impl FromRef<TestState> for PostgresSessionStore {
+        fn from_ref(_: &TestState) -> Self {
+            unreachable!("the session store must not be queried")
+        }
+    }
+
+    impl FromRef<TestState> for Arc<Repositories> {
+        fn from_ref(_: &TestState) -> Self {
+            unreachable!("the repositories must not be queried")
+        }
+    }
+
+    impl FromRef<TestState> for AuthMode {
+        fn from_ref(_: &TestState) -> Self {
+            AuthMode::Session
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_non_base64_bearer_token() {
+        let (mut parts, _) = Request::builder()
+            .header(header::AUTHORIZATION, "Bearer not*base64!")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let rejection = UserContext::from_request_parts(&mut parts, &TestState)
+            .await
+            .err()
+            .expect("malformed token must be rejected");
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    async fn json_rejection(body: &'static str) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = Json::<TokenQuery>::from_request(request, &())
+            .await
+            .err()
+            .expect("body must be rejected")
+            .into_response();
+
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_bad_request() {
+        let (status, body) = json_rejection("{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+    }
+
+    #[tokio::test]
+    async fn mistyped_json_is_validation_error() {
+        let (status, body) = json_rejection(r#"{"token": 1}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "validation");
     }
 }
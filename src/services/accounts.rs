@@ -1,62 +1,273 @@
-use std::env;
+use std::collections::HashSet;
+use std::net::IpAddr;
 use std::time::Duration;
 
 use async_session::{Session, SessionStore};
 // use async_sqlx_session::MySqlSessionStore;
-use async_sqlx_session::PostgresSessionStore;
 
-use crate::constants::{AXUM_SESSION_COOKIE_NAME, AXUM_SESSION_USER_ID_KEY, ENV_KEY_DATABASE_URL};
+use crate::constants::{
+    AXUM_SESSION_CREATED_AT_KEY, AXUM_SESSION_USER_AGENT_KEY, AXUM_SESSION_USER_ID_KEY,
+    PASSWORD_RESET_TTL_SECS, SESSION_TTL_SECS,
+};
 
-use crate::entities::Account;
+use super::boards::leave_all_boards;
+use crate::entities::{Account, LoginSession, PasswordResetToken};
+use crate::error::AppError;
+use crate::events::BoardHub;
+use crate::notifier::Notifier;
+use crate::rate_limit::LoginLimiter;
 use crate::repositories::accounts::Accounts;
+use crate::repositories::boards::Boards;
+use crate::repositories::password_resets::PasswordResets;
+use crate::repositories::refresh_tokens::RefreshTokens;
+use crate::repositories::sessions::Sessions;
+use crate::repositories::tickets::Tickets;
+use crate::request::UserContext;
 
-pub async fn create_account(repo: &impl Accounts, password: &str, display_name: &str) {
-    let new_account = Account::create(password, display_name);
-    repo.store(&new_account).await;
+pub async fn create_account(
+    repo: &impl Accounts,
+    password: &str,
+    display_name: &str,
+) -> Result<(), AppError> {
+    let new_account = Account::create(password, display_name)?;
+    // 同時登録は accounts.display_name の UNIQUE 制約でも弾かれる
+    if repo.find_by(display_name).await?.is_some() {
+        return Err(AppError::conflict("Display name is already taken"));
+    }
+    repo.store(&new_account).await?;
+    Ok(())
 }
 
-pub async fn create_session(
+pub struct Credentials<'a> {
+    pub display_name: &'a str,
+    pub password: &'a str,
+    pub user_agent: Option<&'a str>,
+    pub ip: Option<IpAddr>,
+}
+
+// ログイン試行の制限を確認した上で資格情報を検証し、アカウントIDを返す
+pub async fn authenticate(
     repo: &impl Accounts,
-    display_name: &str,
-    password: &str,
-) -> Option<SessionToken> {
-    let account = repo.find_by(display_name).await;
-    if let Some(account) = account {
-        if !account.matches_password(password) {
-            return None;
+    limiter: &LoginLimiter,
+    credentials: &Credentials<'_>,
+) -> Result<i64, AppError> {
+    let Credentials {
+        display_name,
+        password,
+        ip,
+        ..
+    } = *credentials;
+    limiter.check(display_name, ip).await?;
+
+    // アカウントの有無を区別させないため同じエラーを返す
+    let account = match repo.find_by(display_name).await? {
+        Some(account) if account.matches_password(password)? => account,
+        _ => {
+            limiter.record_failure(display_name, ip).await?;
+            return Err(AppError::Unauthorized(
+                "Invalid display name or password".to_string(),
+            ));
         }
+    };
+    limiter.record_success(display_name).await?;
 
-        dotenv::dotenv().ok();
-        let database_url = env::var(ENV_KEY_DATABASE_URL).unwrap();
+    account
+        .id()
+        .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))
+}
 
-        // PostgresSessionStore に変更
-        let store = PostgresSessionStore::new(&database_url)
-            .await
-            .expect("failed to create PostgresSessionStore");
+pub async fn create_session(
+    store: &impl SessionStore,
+    account_id: i64,
+    user_agent: Option<&str>,
+) -> Result<SessionToken, AppError> {
+    let mut session = Session::new();
+    session
+        .insert(AXUM_SESSION_USER_ID_KEY, account_id)
+        .and_then(|_| session.insert(AXUM_SESSION_CREATED_AT_KEY, chrono::Utc::now()))
+        .and_then(|_| match user_agent {
+            Some(user_agent) => session.insert(AXUM_SESSION_USER_AGENT_KEY, user_agent),
+            None => Ok(()),
+        })
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    session.expire_in(Duration::from_secs(SESSION_TTL_SECS));
 
-        let mut session = Session::new();
-        session
-            .insert(AXUM_SESSION_USER_ID_KEY, account.id().unwrap())
-            .unwrap();
-        session.expire_in(Duration::from_secs(604800));
+    let cookie = store
+        .store_session(session)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::Database("Session cookie was not issued".to_string()))?;
 
-        let cookie = store.store_session(session).await.unwrap().unwrap();
+    Ok(SessionToken(cookie))
+}
+
+//ログアウト（現在のセッションを破棄）
+pub async fn delete_session(repo: &impl Sessions, user: &UserContext) -> Result<(), AppError> {
+    user.require_login()?;
+    revoke_session(repo, user, &user.session_id).await
+}
 
-        Some(SessionToken(cookie))
+//ログイン中のセッション一覧
+pub async fn get_sessions(
+    repo: &impl Sessions,
+    user: &UserContext,
+) -> Result<Vec<LoginSession>, AppError> {
+    user.require_login()?;
+    repo.find_by_account(user.user_id).await
+}
+
+//セッション失効（自分のセッションのみ）
+pub async fn revoke_session(
+    repo: &impl Sessions,
+    user: &UserContext,
+    session_id: &str,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    if repo.delete(user.user_id, session_id).await? {
+        Ok(())
     } else {
-        None
+        Err(AppError::NotFound("Session not found".to_string()))
+    }
+}
+
+//パスワード変更（変更後は全セッション・リフレッシュトークンを失効させる）
+pub async fn change_password(
+    accounts_repo: &impl Accounts,
+    sessions_repo: &impl Sessions,
+    refresh_repo: &impl RefreshTokens,
+    user: &UserContext,
+    current_password: &str,
+    new_password: &str,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    let mut account = find_account(accounts_repo, user.user_id).await?;
+    if !account.matches_password(current_password)? {
+        return Err(AppError::Unauthorized(
+            "Current password is incorrect".to_string(),
+        ));
+    }
+
+    account.change_password(new_password)?;
+    accounts_repo.update_password(&account).await?;
+    sessions_repo.delete_by_account(user.user_id).await?;
+    refresh_repo.delete_by_account(user.user_id).await?;
+    Ok(())
+}
+
+//パスワードリセット発行（管理者のみ）
+pub async fn request_password_reset(
+    accounts_repo: &impl Accounts,
+    resets_repo: &impl PasswordResets,
+    notifier: &dyn Notifier,
+    user: &UserContext,
+    display_name: &str,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    let actor = find_account(accounts_repo, user.user_id).await?;
+    if !actor.is_admin {
+        return Err(AppError::Forbidden(
+            "Only administrators can reset passwords".to_string(),
+        ));
     }
+
+    let account = accounts_repo
+        .find_by(display_name)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
+    let account_id = account
+        .id()
+        .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))?;
+
+    let (token, entity) = PasswordResetToken::issue(
+        account_id,
+        chrono::Duration::seconds(PASSWORD_RESET_TTL_SECS),
+    );
+    resets_repo.store(&entity).await?;
+    notifier.send_password_reset(&account, &token).await
+}
+
+//リセットトークンによるパスワード再設定
+pub async fn reset_password(
+    accounts_repo: &impl Accounts,
+    resets_repo: &impl PasswordResets,
+    sessions_repo: &impl Sessions,
+    refresh_repo: &impl RefreshTokens,
+    token: &str,
+    new_password: &str,
+) -> Result<(), AppError> {
+    // 先に形式を検証し、弱いパスワードでトークンを使い切らないようにする
+    Account::validate_new_password(new_password)?;
+
+    let account_id = resets_repo
+        .consume(&PasswordResetToken::hash(token))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Reset token is invalid or expired".to_string()))?;
+
+    let mut account = find_account(accounts_repo, account_id).await?;
+    account.change_password(new_password)?;
+    accounts_repo.update_password(&account).await?;
+    sessions_repo.delete_by_account(account_id).await?;
+    refresh_repo.delete_by_account(account_id).await?;
+    Ok(())
+}
+
+//ログイン中のアカウント取得
+pub async fn get_account(repo: &impl Accounts, user: &UserContext) -> Result<Account, AppError> {
+    find_account(repo, user.user_id).await
+}
+
+//表示名変更
+pub async fn change_display_name(
+    repo: &impl Accounts,
+    user: &UserContext,
+    display_name: &str,
+) -> Result<Account, AppError> {
+    user.require_login()?;
+    let mut account = find_account(repo, user.user_id).await?;
+    if account.display_name == display_name {
+        return Ok(account);
+    }
+
+    account.change_display_name(display_name)?;
+    // 同時変更は accounts.display_name の UNIQUE 制約でも弾かれる
+    if repo.find_by(display_name).await?.is_some() {
+        return Err(AppError::conflict("Display name is already taken"));
+    }
+    repo.update_display_name(&account).await?;
+    Ok(account)
+}
+
+//アカウント削除（ボード・チケットからの参照を残すためアカウントは匿名化する）
+pub async fn delete_account(
+    accounts_repo: &impl Accounts,
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    sessions_repo: &impl Sessions,
+    hub: &BoardHub,
+    user: &UserContext,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    let mut account = find_account(accounts_repo, user.user_id).await?;
+
+    leave_all_boards(boards_repo, tickets_repo, hub, user.user_id).await?;
+    account.anonymize()?;
+    accounts_repo.anonymize(&account).await?;
+    sessions_repo.delete_by_account(user.user_id).await?;
+    Ok(())
+}
+
+// 削除済みのアカウントは見つからない扱い
+async fn find_account(repo: &impl Accounts, account_id: i64) -> Result<Account, AppError> {
+    repo.find(HashSet::from([account_id]))
+        .await?
+        .remove(&account_id)
+        .filter(|account| account.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound("Account not found".to_string()))
 }
 
 pub struct SessionToken(String);
 
 impl SessionToken {
-    pub fn cookie(&self) -> String {
-        format!(
-            "{}={}; Max-Age=604800; Path=/; HttpOnly",
-            AXUM_SESSION_COOKIE_NAME, &self.0
-        )
-    }
     pub fn value(&self) -> &str {
         &self.0
     }
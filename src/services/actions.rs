@@ -0,0 +1,155 @@
+use super::boards::{board_role, find_board_with_role};
+use crate::controllers::boards::{ActionItemForm, ActionItemPatch};
+use crate::entities::{ActionItem, Board};
+use crate::error::AppError;
+use crate::policy::{Policy, RolePolicy};
+use crate::repositories::action_items::ActionItems;
+use crate::repositories::boards::Boards;
+use crate::repositories::tickets::Tickets;
+use crate::request::UserContext;
+
+//ボードのアクションアイテム一覧
+pub async fn get_board_action_items(
+    boards_repo: &impl Boards,
+    actions_repo: &impl ActionItems,
+    user: &UserContext,
+    board_id: i64,
+) -> Result<Vec<ActionItem>, AppError> {
+    find_board_with_role(boards_repo, user, board_id).await?;
+    actions_repo.find_by_board_id(board_id).await
+}
+
+//自分が担当のアクションアイテム一覧（全ボード）
+pub async fn get_my_action_items(
+    actions_repo: &impl ActionItems,
+    user: &UserContext,
+) -> Result<Vec<ActionItem>, AppError> {
+    if !RolePolicy.can_list_boards(user) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to list action items".to_string(),
+        ));
+    }
+    actions_repo.find_by_assignee(user.user_id).await
+}
+
+//Try のチケットからアクションアイテム作成（close 後のボードでも作成できる）
+pub async fn create_action_item(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    actions_repo: &impl ActionItems,
+    user: &UserContext,
+    board_id: i64,
+    form: &ActionItemForm,
+) -> Result<ActionItem, AppError> {
+    let (board, role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if !RolePolicy.can_manage_actions(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to add action items to this board".to_string(),
+        ));
+    }
+
+    // 別ボードのチケットIDを指定された場合は見つからない扱い
+    let ticket = tickets_repo
+        .find(form.ticket_id)
+        .await?
+        .filter(|t| t.board_id == board_id)
+        .ok_or_else(|| AppError::NotFound("Ticket not found".to_string()))?;
+    ensure_assignable(boards_repo, &board, form.assignee_id).await?;
+
+    let mut action = ActionItem::create(
+        &ticket,
+        user.user_id,
+        form.title.clone(),
+        form.assignee_id,
+        form.due_date,
+    )?;
+    action.id = Some(actions_repo.store(&action).await?);
+    Ok(action)
+}
+
+//アクションアイテム更新（指定された項目のみ、担当者は自分の担当分を更新できる）
+pub async fn update_action_item(
+    boards_repo: &impl Boards,
+    actions_repo: &impl ActionItems,
+    user: &UserContext,
+    board_id: i64,
+    action_id: i64,
+    patch: &ActionItemPatch,
+) -> Result<ActionItem, AppError> {
+    let (board, role) = find_board_with_role(boards_repo, user, board_id).await?;
+    let mut action = find_action_on_board(actions_repo, action_id, board_id).await?;
+    // 担当者は状態のみ変更できる
+    let edits_content =
+        patch.title.is_some() || patch.assignee_id.is_some() || patch.due_date.is_some();
+    let allowed = if edits_content {
+        RolePolicy.can_manage_actions(user, role)
+    } else {
+        RolePolicy.can_update_action_status(user, role, &action)
+    };
+    if !allowed {
+        return Err(AppError::Forbidden(
+            "Unauthorized to update this action item".to_string(),
+        ));
+    }
+
+    if let Some(title) = &patch.title {
+        action.change_title(title.clone())?;
+    }
+    if let Some(assignee_id) = patch.assignee_id {
+        ensure_assignable(boards_repo, &board, assignee_id).await?;
+        action.assignee_id = assignee_id;
+    }
+    if let Some(due_date) = patch.due_date {
+        action.due_date = due_date;
+    }
+    if let Some(status) = patch.status {
+        action.status = status;
+    }
+    actions_repo.update(&action).await?;
+    Ok(action)
+}
+
+//アクションアイテム削除
+pub async fn delete_action_item(
+    boards_repo: &impl Boards,
+    actions_repo: &impl ActionItems,
+    user: &UserContext,
+    board_id: i64,
+    action_id: i64,
+) -> Result<(), AppError> {
+    let (_, role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if !RolePolicy.can_manage_actions(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to delete this action item".to_string(),
+        ));
+    }
+    find_action_on_board(actions_repo, action_id, board_id).await?;
+
+    actions_repo.delete(action_id).await
+}
+
+// 担当者はボードのメンバーに限る
+async fn ensure_assignable(
+    boards_repo: &impl Boards,
+    board: &Board,
+    assignee_id: i64,
+) -> Result<(), AppError> {
+    match board_role(boards_repo, board, assignee_id).await? {
+        Some(_) => Ok(()),
+        None => Err(AppError::validation(
+            "Assignee must be a member of this board",
+        )),
+    }
+}
+
+// 別ボードのアクションアイテムIDを指定された場合は見つからない扱い
+async fn find_action_on_board(
+    repo: &impl ActionItems,
+    action_id: i64,
+    board_id: i64,
+) -> Result<ActionItem, AppError> {
+    repo.find(action_id)
+        .await?
+        .filter(|a| a.board_id == board_id)
+        .ok_or_else(|| AppError::NotFound("Action item not found".to_string()))
+}
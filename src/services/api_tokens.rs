@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+
+use crate::entities::{ApiScope, ApiToken};
+use crate::error::AppError;
+use crate::repositories::api_tokens::ApiTokens;
+use crate::request::UserContext;
+
+//個人用トークン発行（平文のトークンはこのときだけ返す）
+pub async fn create_api_token(
+    repo: &impl ApiTokens,
+    user: &UserContext,
+    name: &str,
+    scopes: Option<Vec<ApiScope>>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(String, ApiToken), AppError> {
+    user.require_login()?;
+
+    let (token, mut entity) = ApiToken::issue(user.user_id, name, scopes, expires_at)?;
+    entity.id = Some(repo.store(&entity).await?);
+    Ok((token, entity))
+}
+
+//個人用トークン一覧
+pub async fn get_api_tokens(
+    repo: &impl ApiTokens,
+    user: &UserContext,
+) -> Result<Vec<ApiToken>, AppError> {
+    user.require_login()?;
+    repo.find_by_account(user.user_id).await
+}
+
+//個人用トークン失効（自分のトークンのみ）
+pub async fn revoke_api_token(
+    repo: &impl ApiTokens,
+    user: &UserContext,
+    token_id: i64,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    if repo.delete(user.user_id, token_id).await? {
+        Ok(())
+    } else {
+        Err(AppError::NotFound("API token not found".to_string()))
+    }
+}
@@ -1,13 +1,26 @@
-use crate::controllers::boards::BoardSummary;
-use crate::entities::Board;
+use crate::controllers::boards::{BoardSummary, MemberSummary};
+use crate::entities::{
+    Board, BoardColumn, BoardMember, BoardPhase, BoardRole, BoardSettings, RetroTemplate,
+};
+use crate::error::AppError;
+use crate::events::{BoardEvent, BoardHub};
+use crate::policy::{Policy, RolePolicy};
+use crate::repositories::accounts::Accounts;
 use crate::repositories::boards::Boards;
+use crate::repositories::tickets::Tickets;
 use crate::request::UserContext;
-use axum::Json;
-use std::convert::TryInto;
+use std::collections::HashSet;
 
-pub async fn get_all_boards(repo: &impl Boards, user: &UserContext) -> Json<Vec<BoardSummary>> {
-    let user_id = user.user_id.try_into().unwrap();
-    let boards = repo.find_by_user_id(user_id).await.unwrap();
+pub async fn get_all_boards(
+    repo: &impl Boards,
+    user: &UserContext,
+) -> Result<Vec<BoardSummary>, AppError> {
+    if !RolePolicy.can_list_boards(user) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to list boards".to_string(),
+        ));
+    }
+    let boards = repo.find_by_user_id(user.user_id).await?;
 
     let summaries = boards
         .into_iter()
@@ -17,68 +30,380 @@ pub async fn get_all_boards(repo: &impl Boards, user: &UserContext) -> Json<Vec<
         })
         .collect();
 
-    Json(summaries)
+    Ok(summaries)
 }
 
 pub async fn get_board_by_id(
     repo: &impl Boards,
     user: &UserContext,
     board_id: i64,
-) -> Result<Board, String> {
-    let boards = repo.find_by_board_id(board_id).await?; // Result を ? で処理
-
-    if let Some(board) = boards.into_iter().next() {
-        if board.created_by == user.user_id || !board.is_deleted() {
-            return Ok(board);
-        }
-    }
-
-    Err("Board not found or access denied".to_string())
+) -> Result<Board, AppError> {
+    let (board, _) = find_board_with_role(repo, user, board_id).await?;
+    Ok(board)
 }
 
 pub async fn save_board(
     repo: &impl Boards,
     user: &UserContext,
     title: String,
-) -> Result<i64, String> {
+    template: RetroTemplate,
+) -> Result<i64, AppError> {
+    if !RolePolicy.can_create_board(user) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to create a board".to_string(),
+        ));
+    }
+
     let mut board = Board::create(title, user.user_id);
-    let bored_id = repo.store(&board).await?;
-    board.id = Some(bored_id);
-    Ok(board.id.unwrap())
+    let board_id = repo.store(&board).await?;
+    board.id = Some(board_id);
+
+    // 作成者をオーナーとして登録
+    repo.add_member(&BoardMember::create(
+        board_id,
+        user.user_id,
+        BoardRole::Owner,
+    ))
+    .await?;
+
+    // テンプレートの列を作成
+    repo.add_columns(&BoardColumn::from_template(board_id, template))
+        .await?;
+
+    Ok(board_id)
+}
+
+//ボードの列（表示順）
+pub async fn get_board_columns(
+    repo: &impl Boards,
+    user: &UserContext,
+    board_id: i64,
+) -> Result<Vec<BoardColumn>, AppError> {
+    find_board_with_role(repo, user, board_id).await?;
+    repo.find_columns(board_id).await
 }
 
+// board.version を期待する版として更新する（他の更新が先に入っていれば Conflict）
 pub async fn update_board(
     repo: &impl Boards,
     user: &UserContext,
     board: &mut Board,
     new_title: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     if board.id.is_none() {
-        return Err("Board ID is required".to_string());
+        return Err(AppError::validation("Board ID is required"));
     }
-    if board.created_by != user.user_id {
-        return Err("Unauthorized to update this board".to_string());
+    let role = board_role(repo, board, user.user_id).await?;
+    if !RolePolicy.can_edit_board(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to update this board".to_string(),
+        ));
     }
+    ensure_open(board)?;
     board.update(new_title);
-    repo.update(board).await
+    match repo.update(board).await? {
+        Some(version) => {
+            board.version = version;
+            Ok(())
+        }
+        None => Err(AppError::conflict(
+            "Board has been modified by someone else",
+        )),
+    }
 }
 
+//ボード設定の変更（オーナーのみ）
+pub async fn update_board_settings(
+    repo: &impl Boards,
+    hub: &BoardHub,
+    user: &UserContext,
+    board_id: i64,
+    settings: BoardSettings,
+) -> Result<BoardSettings, AppError> {
+    let (mut board, role) = find_board_with_role(repo, user, board_id).await?;
+    if !RolePolicy.can_change_settings(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to change settings of this board".to_string(),
+        ));
+    }
+    ensure_open(&board)?;
+    settings.validate()?;
+
+    board.settings = settings;
+    repo.update_settings(&board).await?;
+
+    hub.publish(board_id, BoardEvent::SettingsUpdated { settings });
+    Ok(settings)
+}
+
+//フェーズの変更（設定と同じくオーナーのみ、phase 省略時は次のフェーズへ進める）
+pub async fn change_board_phase(
+    repo: &impl Boards,
+    hub: &BoardHub,
+    user: &UserContext,
+    board_id: i64,
+    phase: Option<BoardPhase>,
+) -> Result<BoardPhase, AppError> {
+    let (mut board, role) = find_board_with_role(repo, user, board_id).await?;
+    if !RolePolicy.can_change_settings(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to change the phase of this board".to_string(),
+        ));
+    }
+
+    let phase = match phase {
+        Some(phase) => phase,
+        None => board
+            .phase
+            .next()
+            .ok_or_else(|| AppError::validation("This board is already closed"))?,
+    };
+    if !board.phase.can_transition_to(phase) {
+        return Err(AppError::validation(format!(
+            "Cannot change phase from {} to {}",
+            board.phase.as_str(),
+            phase.as_str()
+        )));
+    }
+
+    board.phase = phase;
+    repo.update_phase(&board).await?;
+
+    hub.publish(board_id, BoardEvent::PhaseChanged { phase });
+    Ok(phase)
+}
+
+// close 後のボードは読み取り専用
+pub(crate) fn ensure_open(board: &Board) -> Result<(), AppError> {
+    if board.is_closed() {
+        return Err(AppError::Forbidden("This board is closed".to_string()));
+    }
+    Ok(())
+}
+
+// チケットも合わせて論理削除する
 pub async fn delete_board(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    user: &UserContext,
+    board_id: i64,
+) -> Result<(), AppError> {
+    let (_, role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if !RolePolicy.can_delete_board(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to delete this board".to_string(),
+        ));
+    }
+
+    for ticket in tickets_repo.find_by_board_id(board_id).await? {
+        if let Some(ticket_id) = ticket.id {
+            tickets_repo.delete(ticket_id).await?;
+        }
+    }
+
+    boards_repo.delete(board_id).await?;
+    hub.publish(board_id, BoardEvent::BoardDeleted);
+    Ok(())
+}
+
+// アカウント削除時：他にメンバーがいないボードは削除し、共有ボードからは抜ける
+// 残るメンバーにオーナーがいなければ最も古いメンバーをオーナーにする
+// 作成者は常にオーナー扱いのため、作成者が抜ける場合は残るオーナーに作成者を付け替える
+pub async fn leave_all_boards(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    account_id: i64,
+) -> Result<(), AppError> {
+    for mut board in boards_repo.find_by_user_id(account_id).await? {
+        let Some(board_id) = board.id else {
+            continue;
+        };
+        let others: Vec<BoardMember> = boards_repo
+            .find_members(board_id)
+            .await?
+            .into_iter()
+            .filter(|m| m.account_id != account_id)
+            .collect();
+
+        let Some(successor) = others.first() else {
+            for ticket in tickets_repo.find_by_board_id(board_id).await? {
+                if let Some(ticket_id) = ticket.id {
+                    tickets_repo.delete(ticket_id).await?;
+                }
+            }
+            boards_repo.delete(board_id).await?;
+            hub.publish(board_id, BoardEvent::BoardDeleted);
+            continue;
+        };
+
+        let owner_id = match others.iter().find(|m| m.role == BoardRole::Owner) {
+            Some(owner) => owner.account_id,
+            None => {
+                boards_repo
+                    .add_member(&BoardMember::create(
+                        board_id,
+                        successor.account_id,
+                        BoardRole::Owner,
+                    ))
+                    .await?;
+                successor.account_id
+            }
+        };
+        if board.created_by == account_id {
+            board.created_by = owner_id;
+            boards_repo.update_creator(&board).await?;
+        }
+        boards_repo.remove_member(board_id, account_id).await?;
+        hub.publish(board_id, BoardEvent::MemberRemoved { account_id });
+    }
+
+    Ok(())
+}
+
+//メンバー一覧取得
+pub async fn get_board_members(
+    boards_repo: &impl Boards,
+    accounts_repo: &impl Accounts,
+    user: &UserContext,
+    board_id: i64,
+) -> Result<Vec<MemberSummary>, AppError> {
+    let board = get_board_by_id(boards_repo, user, board_id).await?;
+    let mut members = boards_repo.find_members(board_id).await?;
+
+    // メンバー登録前に作られたボードは作成者をオーナーとして補う
+    if !members.iter().any(|m| m.account_id == board.created_by) {
+        members.insert(
+            0,
+            BoardMember::new(
+                board_id,
+                board.created_by,
+                BoardRole::Owner,
+                board.created_at,
+            ),
+        );
+    }
+
+    let ids: HashSet<i64> = members.iter().map(|m| m.account_id).collect();
+    let accounts = accounts_repo.find(ids).await?;
+
+    Ok(members
+        .into_iter()
+        .map(|m| MemberSummary {
+            account_id: m.account_id,
+            display_name: accounts
+                .get(&m.account_id)
+                .map(|a| a.display_name.clone())
+                .unwrap_or_default(),
+            role: m.role,
+        })
+        .collect())
+}
+
+//メンバー追加（ロール変更）
+pub async fn add_board_member(
+    boards_repo: &impl Boards,
+    accounts_repo: &impl Accounts,
+    user: &UserContext,
+    board_id: i64,
+    display_name: &str,
+    role: BoardRole,
+) -> Result<MemberSummary, AppError> {
+    let (board, user_role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if !RolePolicy.can_manage_members(user, user_role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to manage members of this board".to_string(),
+        ));
+    }
+
+    let account = accounts_repo
+        .find_by(display_name)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
+    let account_id = account
+        .id()
+        .ok_or_else(|| AppError::Database("Account ID is not set".to_string()))?;
+
+    if account_id == board.created_by {
+        return Err(AppError::validation(
+            "The board creator's role cannot be changed",
+        ));
+    }
+
+    boards_repo
+        .add_member(&BoardMember::create(board_id, account_id, role))
+        .await?;
+
+    Ok(MemberSummary {
+        account_id,
+        display_name: account.display_name,
+        role,
+    })
+}
+
+//メンバー削除（本人による退出も可）
+pub async fn remove_board_member(
+    boards_repo: &impl Boards,
+    hub: &BoardHub,
+    user: &UserContext,
+    board_id: i64,
+    account_id: i64,
+) -> Result<(), AppError> {
+    let (board, user_role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if account_id == board.created_by {
+        return Err(AppError::validation("The board creator cannot be removed"));
+    }
+    let allowed = if account_id == user.user_id {
+        RolePolicy.can_leave_board(user, user_role)
+    } else {
+        RolePolicy.can_manage_members(user, user_role)
+    };
+    if !allowed {
+        return Err(AppError::Forbidden(
+            "Unauthorized to manage members of this board".to_string(),
+        ));
+    }
+
+    if !boards_repo.remove_member(board_id, account_id).await? {
+        return Err(AppError::NotFound("Member not found".to_string()));
+    }
+    hub.publish(board_id, BoardEvent::MemberRemoved { account_id });
+    Ok(())
+}
+
+// 閲覧権限を確認した上でボードとロールを返す
+// 権限がない場合もボードの存在を知られないよう NotFound とする
+pub(crate) async fn find_board_with_role(
     repo: &impl Boards,
     user: &UserContext,
     board_id: i64,
-) -> Result<(), String> {
-    let board = repo
-        .find(board_id)
-        .await
-        .map_err(|e| format!("DB error: {}", e))?
-        .ok_or_else(|| "Board not found".to_string())?;
+) -> Result<(Board, Option<BoardRole>), AppError> {
+    let boards = repo.find_by_board_id(board_id).await?; // Result を ? で処理
 
-    if board.created_by != user.user_id {
-        return Err("Unauthorized to delete this board".to_string());
+    if let Some(board) = boards.into_iter().next() {
+        let role = board_role(repo, &board, user.user_id).await?;
+        if RolePolicy.can_read_board(user, role) {
+            return Ok((board, role));
+        }
     }
 
-    repo.delete(board_id)
-        .await
-        .map_err(|e| format!("Failed to delete board: {}", e))
+    Err(AppError::NotFound(
+        "Board not found or access denied".to_string(),
+    ))
+}
+
+// 作成者は常にオーナー、それ以外は board_member のロール
+pub(crate) async fn board_role(
+    repo: &impl Boards,
+    board: &Board,
+    user_id: i64,
+) -> Result<Option<BoardRole>, AppError> {
+    if board.created_by == user_id {
+        return Ok(Some(BoardRole::Owner));
+    }
+    match board.id {
+        Some(board_id) => repo.find_member_role(board_id, user_id).await,
+        None => Ok(None),
+    }
 }
@@ -0,0 +1,93 @@
+use crate::auth::JwtKeys;
+use crate::entities::{LoginSession, RefreshToken};
+use crate::error::AppError;
+use crate::repositories::refresh_tokens::RefreshTokens;
+use crate::request::UserContext;
+
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    // アクセストークンの有効秒数
+    pub expires_in: i64,
+}
+
+// family_id を指定した場合は既存の系列を引き継ぐ（リフレッシュ時）
+pub async fn issue_token_pair(
+    repo: &impl RefreshTokens,
+    keys: &JwtKeys,
+    account_id: i64,
+    family_id: Option<String>,
+) -> Result<TokenPair, AppError> {
+    let (refresh_token, entity) = RefreshToken::issue(account_id, family_id, keys.refresh_ttl);
+    repo.store(&entity).await?;
+
+    Ok(TokenPair {
+        access_token: keys.issue(account_id, &entity.family_id)?,
+        refresh_token,
+        expires_in: keys.access_ttl.num_seconds(),
+    })
+}
+
+// リフレッシュトークンを使用済みにして新しい組を発行する
+// 使用済みのトークンが再び使われた場合は漏洩とみなし系列ごと失効させる
+pub async fn refresh_token_pair(
+    repo: &impl RefreshTokens,
+    keys: &JwtKeys,
+    refresh_token: &str,
+) -> Result<TokenPair, AppError> {
+    let invalid = || AppError::Unauthorized("Refresh token is invalid or expired".to_string());
+
+    let token_hash = RefreshToken::hash(refresh_token);
+    let entity = repo.find(&token_hash).await?.ok_or_else(invalid)?;
+
+    if entity.used_at.is_some() || !repo.mark_used(&token_hash).await? {
+        tracing::warn!(
+            "refresh token reuse detected for account {}, revoking family",
+            entity.account_id
+        );
+        repo.delete_family(entity.account_id, &entity.family_id)
+            .await?;
+        return Err(AppError::Unauthorized(
+            "Refresh token has already been used".to_string(),
+        ));
+    }
+    if entity.expires_at <= chrono::Utc::now() {
+        return Err(invalid());
+    }
+
+    issue_token_pair(repo, keys, entity.account_id, Some(entity.family_id)).await
+}
+
+//ログアウト（JWTモード：リフレッシュトークンの系列を失効）
+// 発行済みのアクセストークンは有効期限まで使える
+pub async fn revoke_token_family(
+    repo: &impl RefreshTokens,
+    user: &UserContext,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    repo.delete_family(user.user_id, &user.session_id).await?;
+    Ok(())
+}
+
+//ログイン中のセッション一覧（JWTモード：リフレッシュトークンの系列）
+pub async fn get_token_families(
+    repo: &impl RefreshTokens,
+    user: &UserContext,
+) -> Result<Vec<LoginSession>, AppError> {
+    user.require_login()?;
+    repo.find_families(user.user_id).await
+}
+
+//セッション失効（JWTモード：自分の系列のみ）
+pub async fn revoke_token_family_by_id(
+    repo: &impl RefreshTokens,
+    user: &UserContext,
+    family_id: &str,
+) -> Result<(), AppError> {
+    user.require_login()?;
+    if repo.delete_family(user.user_id, family_id).await? > 0 {
+        Ok(())
+    } else {
+        Err(AppError::NotFound("Session not found".to_string()))
+    }
+}
@@ -1,50 +1,432 @@
+use std::collections::{HashMap, HashSet};
 
-use crate::entities::Ticket;
+use super::boards::{ensure_open, find_board_with_role};
+use crate::entities::{Board, BoardPhase, Category, Ticket, TicketVote};
+use crate::error::AppError;
+use crate::events::{BoardEvent, BoardHub};
+use crate::policy::{Policy, RolePolicy};
+use crate::repositories::accounts::Accounts;
+use crate::repositories::boards::Boards;
 use crate::repositories::tickets::Tickets;
 use crate::request::UserContext;
 
 //チケットすべて取得
 pub async fn get_all_tickets(
-    repo: &impl Tickets,
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
     user: &UserContext,
     board_id: i64,
-) -> Result<Vec<Ticket>, String> {
-    let tickets = repo.find_by_board_id(board_id).await?;
+) -> Result<Vec<Ticket>, AppError> {
+    let (board, _) = find_board_with_role(boards_repo, user, board_id).await?;
+    let mut tickets = tickets_repo.find_by_board_id(board_id).await?;
+    for ticket in &mut tickets {
+        ticket.conceal_author(&board);
+    }
     Ok(tickets)
 }
 
+//チケット作成者の表示名（まとめて1回で取得、削除済みのアカウントと匿名のチケットは含まない）
+pub async fn get_ticket_authors(
+    accounts_repo: &impl Accounts,
+    tickets: &[Ticket],
+) -> Result<HashMap<i64, String>, AppError> {
+    let ids: HashSet<i64> = tickets
+        .iter()
+        .filter(|t| !t.is_author_concealed())
+        .map(|t| t.author_id)
+        .collect();
+    let accounts = accounts_repo.find(ids).await?;
+
+    Ok(accounts
+        .into_iter()
+        .filter(|(_, account)| account.deleted_at.is_none())
+        .map(|(id, account)| (id, account.display_name))
+        .collect())
+}
+
+//チケット1件取得
+pub async fn get_ticket(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    user: &UserContext,
+    ticket_id: i64,
+) -> Result<Ticket, AppError> {
+    let mut ticket = tickets_repo
+        .find(ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket not found".to_string()))?;
+    let (board, _) = find_board_with_role(boards_repo, user, ticket.board_id).await?;
+    ticket.conceal_author(&board);
+    Ok(ticket)
+}
+
 //チケット保存
 pub async fn save_ticket(
-    repo: &impl Tickets,
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
     user: &UserContext,
-    ticket: Ticket,
-) -> Result<(), String> {
+    mut ticket: Ticket,
+) -> Result<Ticket, AppError> {
     if ticket.id.is_some() {
-        return Err("Ticket ID should not be set for new tickets".to_string());
+        return Err(AppError::validation(
+            "Ticket ID should not be set for new tickets",
+        ));
+    }
+    if ticket.author_id != user.user_id {
+        return Err(AppError::Forbidden(
+            "Ticket author must be the current user".to_string(),
+        ));
+    }
+    let (board, role) = find_board_with_role(boards_repo, user, ticket.board_id).await?;
+    if !RolePolicy.can_create_ticket(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to add tickets to this board".to_string(),
+        ));
     }
-    repo.store(&ticket).await
+    if board.phase != BoardPhase::Write {
+        return Err(AppError::Forbidden(
+            "Tickets can only be added in the write phase".to_string(),
+        ));
+    }
+    ensure_board_column(boards_repo, ticket.board_id, ticket.category).await?;
+    ticket.anonymous = board.settings.anonymous;
+    ticket.id = Some(tickets_repo.store(&ticket).await?);
+    ticket.conceal_author(&board);
+
+    hub.publish(
+        ticket.board_id,
+        BoardEvent::TicketCreated {
+            ticket: ticket.clone(),
+        },
+    );
+    Ok(ticket)
 }
-//チケット更新
+//チケット更新（ticket.version を期待する版とし、古ければ最新のチケットを添えて Conflict）
 pub async fn update_ticket(
-    repo: &impl Tickets,
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
     user: &UserContext,
     ticket: Ticket,
-) -> Result<(), String> {
-    if ticket.id.is_none() {
-        return Err("Ticket ID is required for update".to_string());
+) -> Result<Ticket, AppError> {
+    let ticket_id = ticket
+        .id
+        .ok_or_else(|| AppError::validation("Ticket ID is required for update"))?;
+    let existing = find_ticket_on_board(tickets_repo, ticket_id, ticket.board_id).await?;
+    let (board, role) = find_board_with_role(boards_repo, user, existing.board_id).await?;
+    if !RolePolicy.can_edit_ticket(user, role, &existing) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to update this ticket".to_string(),
+        ));
     }
-    repo.update(&ticket).await
+    ensure_open(&board)?;
+    if ticket.category != existing.category {
+        ensure_board_column(boards_repo, existing.board_id, ticket.category).await?;
+    }
+
+    // 作成者などは既存の値を引き継ぐ
+    let from_category = existing.category;
+    let mut updated = existing.clone();
+    updated.update(ticket.category, ticket.content);
+    updated.version = ticket.version;
+    let Some(version) = tickets_repo.update(&updated).await? else {
+        let mut current = tickets_repo.find(ticket_id).await?.unwrap_or(existing);
+        current.conceal_author(&board);
+        return Err(
+            AppError::conflict("Ticket has been modified by someone else").with_current(current),
+        );
+    };
+    updated.version = version;
+    updated.conceal_author(&board);
+
+    let event = if updated.category != from_category {
+        BoardEvent::TicketMoved {
+            ticket: updated.clone(),
+            from_category,
+            index: None,
+        }
+    } else {
+        BoardEvent::TicketUpdated {
+            ticket: updated.clone(),
+        }
+    };
+    hub.publish(updated.board_id, event);
+    Ok(updated)
 }
+//列のチケットを ids の順に並べ直す（ボード全体の保存で使う）
+pub async fn reorder_tickets(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    user: &UserContext,
+    board_id: i64,
+    ids: &[i64],
+) -> Result<(), AppError> {
+    let (board, role) = find_board_with_role(boards_repo, user, board_id).await?;
+    if !RolePolicy.can_edit_board(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to reorder tickets on this board".to_string(),
+        ));
+    }
+    ensure_open(&board)?;
+
+    let on_board: HashSet<i64> = tickets_repo
+        .find_by_board_id(board_id)
+        .await?
+        .into_iter()
+        .filter_map(|t| t.id)
+        .collect();
+    if let Some(id) = ids.iter().find(|id| !on_board.contains(id)) {
+        return Err(AppError::NotFound(format!("Ticket {} not found", id)));
+    }
+
+    tickets_repo.reorder(ids).await
+}
+
+//チケットを ticket.category の列の index の位置へ移動し、移動先の列の並び順を振り直す
+// （ticket.version を期待する版とし、古ければ最新のチケットを添えて Conflict）
+pub async fn move_ticket(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    user: &UserContext,
+    ticket: Ticket,
+    index: usize,
+) -> Result<Ticket, AppError> {
+    let ticket_id = ticket
+        .id
+        .ok_or_else(|| AppError::validation("Ticket ID is required for move"))?;
+    let category = ticket.category;
+    let existing = find_ticket_on_board(tickets_repo, ticket_id, ticket.board_id).await?;
+    let (board, role) = find_board_with_role(boards_repo, user, existing.board_id).await?;
+    if !RolePolicy.can_edit_ticket(user, role, &existing) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to move this ticket".to_string(),
+        ));
+    }
+    ensure_open(&board)?;
+    ensure_board_column(boards_repo, existing.board_id, category).await?;
+
+    let from_category = existing.category;
+    let mut moved = existing.clone();
+    moved.update(category, existing.content.clone());
+    moved.version = ticket.version;
+    let Some(version) = tickets_repo.update(&moved).await? else {
+        let mut current = tickets_repo.find(ticket_id).await?.unwrap_or(existing);
+        current.conceal_author(&board);
+        return Err(
+            AppError::conflict("Ticket has been modified by someone else").with_current(current),
+        );
+    };
+    moved.version = version;
+
+    // 移動先の列の並びに差し込む（範囲外の位置は末尾）
+    let mut ids: Vec<i64> = tickets_repo
+        .find_by_board_id(moved.board_id)
+        .await?
+        .into_iter()
+        .filter(|t| t.category == category)
+        .filter_map(|t| t.id)
+        .filter(|id| *id != ticket_id)
+        .collect();
+    let index = index.min(ids.len());
+    ids.insert(index, ticket_id);
+    tickets_repo.reorder(&ids).await?;
+    moved.position = index as i32;
+    moved.conceal_author(&board);
+
+    hub.publish(
+        moved.board_id,
+        BoardEvent::TicketMoved {
+            ticket: moved.clone(),
+            from_category,
+            index: Some(index),
+        },
+    );
+    Ok(moved)
+}
+// ボードの票の集計（投票フェーズが終わるまで票数を隠す設定なら counts を返さない）
+pub struct VoteTally {
+    pub counts: Option<HashMap<i64, i64>>,
+    pub mine: HashMap<i64, i64>,
+    pub remaining: i64,
+}
+
+impl VoteTally {
+    fn new(board: &Board, votes: &[TicketVote], account_id: i64) -> VoteTally {
+        let mut counts = HashMap::new();
+        let mut mine = HashMap::new();
+        for vote in votes {
+            *counts.entry(vote.ticket_id).or_insert(0) += 1;
+            if vote.account_id == account_id {
+                *mine.entry(vote.ticket_id).or_insert(0) += 1;
+            }
+        }
+        let used: i64 = mine.values().sum();
+
+        VoteTally {
+            counts: board.votes_visible().then_some(counts),
+            mine,
+            remaining: (i64::from(board.settings.vote_budget) - used).max(0),
+        }
+    }
+
+    pub fn count(&self, ticket_id: i64) -> Option<i64> {
+        self.counts
+            .as_ref()
+            .map(|counts| counts.get(&ticket_id).copied().unwrap_or(0))
+    }
+
+    pub fn mine(&self, ticket_id: i64) -> i64 {
+        self.mine.get(&ticket_id).copied().unwrap_or(0)
+    }
+}
+
+//ボードの票の集計
+pub async fn get_vote_tally(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    user: &UserContext,
+    board_id: i64,
+) -> Result<VoteTally, AppError> {
+    let (board, _) = find_board_with_role(boards_repo, user, board_id).await?;
+    let votes = tickets_repo.find_votes_by_board_id(board_id).await?;
+    Ok(VoteTally::new(&board, &votes, user.user_id))
+}
+
+//チケットに1票入れる（1人あたりの票数はボードの設定まで）
+pub async fn vote_ticket(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    user: &UserContext,
+    ticket_id: i64,
+) -> Result<VoteTally, AppError> {
+    let board = find_votable_board(boards_repo, tickets_repo, user, ticket_id).await?;
+    let vote = TicketVote::create(ticket_id, user.user_id);
+    if !tickets_repo
+        .add_vote(&vote, board.settings.vote_budget)
+        .await?
+    {
+        return Err(AppError::validation("No votes left on this board"));
+    }
+
+    publish_votes(tickets_repo, hub, user, &board, ticket_id).await
+}
+
+//チケットから自分の票を1つ取り消す
+pub async fn unvote_ticket(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    user: &UserContext,
+    ticket_id: i64,
+) -> Result<VoteTally, AppError> {
+    let board = find_votable_board(boards_repo, tickets_repo, user, ticket_id).await?;
+    if !tickets_repo.remove_vote(ticket_id, user.user_id).await? {
+        return Err(AppError::NotFound("Vote not found".to_string()));
+    }
+
+    publish_votes(tickets_repo, hub, user, &board, ticket_id).await
+}
+
+// 投票・取り消しができるチケットのボード
+async fn find_votable_board(
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    user: &UserContext,
+    ticket_id: i64,
+) -> Result<Board, AppError> {
+    let ticket = tickets_repo
+        .find(ticket_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket not found".to_string()))?;
+    let (board, role) = find_board_with_role(boards_repo, user, ticket.board_id).await?;
+    if !RolePolicy.can_vote(user, role) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to vote on this board".to_string(),
+        ));
+    }
+    if board.phase != BoardPhase::Vote {
+        return Err(AppError::Forbidden(
+            "Voting is only open in the vote phase".to_string(),
+        ));
+    }
+    Ok(board)
+}
+
+async fn publish_votes(
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
+    user: &UserContext,
+    board: &Board,
+    ticket_id: i64,
+) -> Result<VoteTally, AppError> {
+    let board_id = board.id.unwrap_or_default();
+    let votes = tickets_repo.find_votes_by_board_id(board_id).await?;
+    let tally = VoteTally::new(board, &votes, user.user_id);
+
+    hub.publish(
+        board_id,
+        BoardEvent::VotesUpdated {
+            ticket_id,
+            votes: tally.count(ticket_id),
+        },
+    );
+    Ok(tally)
+}
+
 //チケット削除
 pub async fn delete_ticket(
-    repo: &impl Tickets,
+    boards_repo: &impl Boards,
+    tickets_repo: &impl Tickets,
+    hub: &BoardHub,
     user: &UserContext,
     ticket_id: i64,
-) -> Result<(), String> {
-    let ticket = repo
+) -> Result<(), AppError> {
+    let ticket = tickets_repo
         .find(ticket_id)
-        .await
-        .ok_or_else(|| "Ticket not found".to_string())?;
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket not found".to_string()))?;
+    let (board, role) = find_board_with_role(boards_repo, user, ticket.board_id).await?;
+    if !RolePolicy.can_delete_ticket(user, role, &ticket) {
+        return Err(AppError::Forbidden(
+            "Unauthorized to delete this ticket".to_string(),
+        ));
+    }
+    ensure_open(&board)?;
 
-    repo.delete(ticket_id).await
+    tickets_repo.delete(ticket_id).await?;
+
+    hub.publish(ticket.board_id, BoardEvent::TicketDeleted { ticket_id });
+    Ok(())
+}
+
+// 別ボードのチケットIDを指定された場合は見つからない扱い
+async fn find_ticket_on_board(
+    repo: &impl Tickets,
+    ticket_id: i64,
+    board_id: i64,
+) -> Result<Ticket, AppError> {
+    repo.find(ticket_id)
+        .await?
+        .filter(|t| t.board_id == board_id)
+        .ok_or_else(|| AppError::NotFound("Ticket not found".to_string()))
+}
+
+// ボードの列にない分類は受け付けない
+async fn ensure_board_column(
+    repo: &impl Boards,
+    board_id: i64,
+    category: Category,
+) -> Result<(), AppError> {
+    let columns = repo.find_columns(board_id).await?;
+    if columns.iter().any(|column| column.category == category) {
+        Ok(())
+    } else {
+        Err(AppError::validation(format!(
+            "Category {} is not a column of this board",
+            category
+        )))
+    }
 }
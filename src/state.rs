@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use async_sqlx_session::PostgresSessionStore;
+use axum::extract::FromRef;
+
+use crate::auth::AuthMode;
+use crate::database::Repositories;
+use crate::events::BoardHub;
+use crate::notifier::Notifier;
+use crate::oidc::OidcClient;
+use crate::rate_limit::LoginLimiter;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub repos: Arc<Repositories>,
+    pub sessions: PostgresSessionStore,
+    pub hub: BoardHub,
+    pub notifier: Arc<dyn Notifier>,
+    pub login_limiter: LoginLimiter,
+    pub auth: AuthMode,
+    // OIDC 未設定時は None
+    pub oidc: Option<OidcClient>,
+}
+
+impl FromRef<AppState> for Arc<Repositories> {
+    fn from_ref(state: &AppState) -> Self {
+        state.repos.clone()
+    }
+}
+
+impl FromRef<AppState> for PostgresSessionStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.sessions.clone()
+    }
+}
+
+impl FromRef<AppState> for BoardHub {
+    fn from_ref(state: &AppState) -> Self {
+        state.hub.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Notifier> {
+    fn from_ref(state: &AppState) -> Self {
+        state.notifier.clone()
+    }
+}
+
+impl FromRef<AppState> for LoginLimiter {
+    fn from_ref(state: &AppState) -> Self {
+        state.login_limiter.clone()
+    }
+}
+
+impl FromRef<AppState> for AuthMode {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+impl FromRef<AppState> for Option<OidcClient> {
+    fn from_ref(state: &AppState) -> Self {
+        state.oidc.clone()
+    }
+}